@@ -0,0 +1,98 @@
+use chrono::{Local, NaiveTime};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::config::{AutoUpdateMode, AutoUpdatePolicy, UpdateWindow};
+use crate::downloader::{self, Target, TauriProgress};
+use crate::launcher_core::download;
+use crate::state::{AppState, GameStatus};
+use crate::supervisor;
+use crate::logger::log_err;
+
+/// Per-run bookkeeping so we don't notify about the same version twice
+#[derive(Default)]
+pub struct AutoUpdateState {
+    notified_version: Option<String>,
+}
+
+/// Whether `now` falls inside the window. A window like 22:00-04:00 wraps past midnight.
+pub fn in_window(window: &UpdateWindow, now: NaiveTime) -> bool {
+    let parse = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").ok();
+    let (Some(start), Some(end)) = (parse(&window.start), parse(&window.end)) else {
+        // Unparseable window, don't block updates over a typo
+        return true;
+    };
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Background downloads are disabled in `Off` mode and limited to the time window if one is set
+pub fn background_download_allowed(policy: &AutoUpdatePolicy) -> bool {
+    if matches!(policy.mode, AutoUpdateMode::Off) {
+        return false;
+    }
+    match &policy.window {
+        Some(window) => in_window(window, Local::now().time()),
+        None => true,
+    }
+}
+
+/// Apply the configured auto-update policy. Called by the update watcher after each poll.
+pub fn maybe_run(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let policy = { state.config.lock().unwrap().auto_update.clone() };
+    if policy.mode == AutoUpdateMode::Off {
+        return;
+    }
+    if *state.status.lock().unwrap() != GameStatus::UpdateAvailable {
+        return;
+    }
+    let Some(m) = state.manifest.lock().unwrap().clone() else {
+        return;
+    };
+
+    let first_seen = {
+        let mut auto = state.auto_update.lock().unwrap();
+        let first = auto.notified_version.as_deref() != Some(m.latest_version.as_str());
+        auto.notified_version = Some(m.latest_version.clone());
+        first
+    };
+    if first_seen {
+        log_err!(app.emit("update-available", m.latest_version.clone()), "emit update-available");
+    }
+
+    if policy.mode == AutoUpdateMode::Notify || !background_download_allowed(&policy) {
+        return;
+    }
+    // Defer until the player closes the game, we'll retry on the next poll
+    if supervisor::running_instance(app).is_some() {
+        return;
+    }
+
+    match policy.mode {
+        // Busy means a staging download or a manual update is running, try again next poll
+        AutoUpdateMode::DownloadAndInstall => {
+            let _ = downloader::spawn_install(app.clone(), m);
+        }
+        AutoUpdateMode::DownloadOnly => {
+            if download::is_staged(&m.latest_version, &m.checksum) {
+                return;
+            }
+            let targets = [Target::Archive(m.latest_version.clone())];
+            if !downloader::try_begin(app, &targets) {
+                return;
+            }
+
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let res = download::download_archive(&m.game_zip, &m.checksum, &m.latest_version, &TauriProgress::new(&app, "update-progress")).await;
+                downloader::end(&app, &targets);
+                if res.is_ok() {
+                    log_err!(app.emit("update-staged", m.latest_version.clone()), "emit update-staged");
+                }
+            });
+        }
+        AutoUpdateMode::Off | AutoUpdateMode::Notify => {}
+    }
+}
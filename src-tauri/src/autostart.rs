@@ -0,0 +1,130 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Argument the autostart entry passes so the launcher comes up in the tray instead of a window
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+/// A way to have the OS start the launcher when the user logs in
+pub trait AutostartBackend {
+    /// True if an entry exists and starts `exe`
+    fn is_enabled(&self, exe: &Path) -> bool;
+    fn enable(&self, exe: &Path) -> io::Result<()>;
+    /// Removing an entry that doesn't exist is not an error
+    fn disable(&self) -> io::Result<()>;
+}
+
+/// `~/.config/autostart/ganhrong-launcher.desktop`, picked up by GNOME, KDE, XFCE etc.
+pub struct XdgAutostart {
+    dir: PathBuf,
+}
+
+impl XdgAutostart {
+    const FILE_NAME: &'static str = "ganhrong-launcher.desktop";
+
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `$XDG_CONFIG_HOME/autostart`, defaulting to `~/.config/autostart`
+    pub fn from_env() -> Option<Self> {
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+        Some(Self::new(config.join("autostart")))
+    }
+
+    pub fn entry_path(&self) -> PathBuf {
+        self.dir.join(Self::FILE_NAME)
+    }
+
+    fn entry(exe: &Path) -> String {
+        format!(
+            "[Desktop Entry]\nType=Application\nName=GanhRong Launcher\nExec={} {}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+            exec_quote(&exe.to_string_lossy()),
+            MINIMIZED_ARG
+        )
+    }
+}
+
+impl AutostartBackend for XdgAutostart {
+    fn is_enabled(&self, exe: &Path) -> bool {
+        fs::read_to_string(self.entry_path()).is_ok_and(|content| content == Self::entry(exe))
+    }
+
+    fn enable(&self, exe: &Path) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(), Self::entry(exe))
+    }
+
+    fn disable(&self) -> io::Result<()> {
+        match fs::remove_file(self.entry_path()) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+/// Quote an argument for a desktop entry Exec line (Desktop Entry spec, "The Exec key")
+pub fn exec_quote(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    let needs_quotes = arg.is_empty() || arg.chars().any(|c| " \t\n\"'\\><~|&;$*?#()`".contains(c));
+    if !needs_quotes {
+        return arg;
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Backend for this platform, None where we don't have one yet (Windows Run key, macOS login items)
+pub fn backend() -> Option<Box<dyn AutostartBackend>> {
+    #[cfg(target_os = "linux")]
+    {
+        XdgAutostart::from_env().map(|b| Box::new(b) as Box<dyn AutostartBackend>)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// What OS entries (autostart, URL handler) should run. Inside an AppImage current_exe() is a temporary mount,
+/// the AppImage file itself is what survives a reboot.
+pub(crate) fn launcher_exe() -> io::Result<PathBuf> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe()
+}
+
+/// Make the OS entry match `enabled`. Also rewrites an entry left pointing at an old location
+/// when the launcher was moved.
+pub fn reconcile_with(backend: &dyn AutostartBackend, enabled: bool, exe: &Path) -> io::Result<()> {
+    if !enabled {
+        return backend.disable();
+    }
+    if !backend.is_enabled(exe) {
+        log::info!("Enabling launch at startup for {}", exe.display());
+        backend.enable(exe)?;
+    }
+    Ok(())
+}
+
+/// Apply `LauncherConfig.launch_at_startup`. Called at startup and on every `save_config`.
+pub fn reconcile(enabled: bool) -> io::Result<()> {
+    let Some(backend) = backend() else {
+        if enabled {
+            log::warn!("Launch at startup is not supported on this platform yet");
+        }
+        return Ok(());
+    };
+    reconcile_with(backend.as_ref(), enabled, &launcher_exe()?)
+}
@@ -0,0 +1,7 @@
+// Release tooling: builds the archive, file list, delta and manifest for a game build.
+// See `release::cli_main` for usage.
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    std::process::exit(tauri_app_lib::release::cli_main(&args));
+}
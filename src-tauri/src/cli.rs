@@ -0,0 +1,90 @@
+use std::future::Future;
+use std::path::PathBuf;
+use crate::headless;
+use crate::logger;
+use crate::validate::{self, ValidateOptions};
+
+const USAGE: &str = "Usage: launcher --validate-manifest <file.json> [--no-urls] [--hash-archive]";
+
+/// Release builds use the Windows GUI subsystem and start without a console. Attach to the one we
+/// were started from (or open one) so output shows up, unless stdout is already redirected.
+#[cfg(windows)]
+fn attach_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(std_handle: u32) -> isize;
+        fn AttachConsole(process_id: u32) -> i32;
+        fn AllocConsole() -> i32;
+    }
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    // SAFETY: plain kernel32 calls without pointer arguments
+    unsafe {
+        if GetStdHandle(STD_OUTPUT_HANDLE) == 0 && AttachConsole(ATTACH_PARENT_PROCESS) == 0 {
+            AllocConsole();
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// Command line entry points that run without the UI.
+/// Returns the exit code if `args` asked for one, None to start the launcher normally.
+/// Output goes to stdout, on the console we were started from.
+pub fn run(args: &[String]) -> Option<i32> {
+    if args.iter().any(|a| a == "--headless" || a == "--validate-manifest") {
+        attach_console();
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--headless") {
+        let Some(command) = args.get(pos + 1).filter(|a| !a.starts_with("--")) else {
+            eprintln!("{}", headless::USAGE);
+            return Some(headless::EXIT_USAGE);
+        };
+        logger::init();
+        return Some(block_on(headless::run(command, args, crate::MANIFEST_URL)));
+    }
+
+    let pos = args.iter().position(|a| a == "--validate-manifest")?;
+    let Some(file) = args.get(pos + 1).filter(|a| !a.starts_with("--")) else {
+        eprintln!("{}", USAGE);
+        return Some(2);
+    };
+    logger::init();
+    let options = ValidateOptions {
+        check_urls: !args.iter().any(|a| a == "--no-urls"),
+        hash_archive: args.iter().any(|a| a == "--hash-archive"),
+    };
+    Some(validate_manifest_file(PathBuf::from(file), options))
+}
+
+fn block_on<F: Future<Output = i32>>(future: F) -> i32 {
+    match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt.block_on(future),
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            2
+        }
+    }
+}
+
+fn validate_manifest_file(path: PathBuf, options: ValidateOptions) -> i32 {
+    let body = match std::fs::read(&path) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return 2;
+        }
+    };
+    block_on(async {
+        let report = validate::validate_manifest(&body, &options).await;
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize report: {}", e);
+                return 2;
+            }
+        }
+        if report.ok { 0 } else { 1 }
+    })
+}
@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use chrono::NaiveTime;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::launcher_core::launch::{self, LaunchProfile};
+use crate::launcher_core::manifest::GameManifest;
+use crate::logger::log_err;
+use crate::paths;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum CloseBehavior {
@@ -8,40 +16,250 @@ pub enum CloseBehavior {
     Exit,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum AutoUpdateMode {
+    Off,
+    Notify,             // Only tell the player
+    DownloadOnly,       // Stage the archive in cache/, install on next Update click
+    DownloadAndInstall,
+}
+
+/// Local time range ("HH:MM") in which background downloads may run. May wrap past midnight.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateWindow {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AutoUpdatePolicy {
+    pub mode: AutoUpdateMode,
+    #[serde(default)]
+    pub window: Option<UpdateWindow>,
+}
+
+impl Default for AutoUpdatePolicy {
+    fn default() -> Self {
+        Self {
+            mode: AutoUpdateMode::Notify,
+            window: None,
+        }
+    }
+}
+
+/// User-side launch options, on top of what the manifest sets
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LaunchSettings {
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub profiles: Vec<LaunchProfile>,
+    #[serde(default)]
+    pub default_profile: Option<String>,
+}
+
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LauncherConfig {
+    #[serde(default = "current_config_version")]
+    pub version: u32,
     pub language: String,
     pub close_behavior: CloseBehavior,
     pub launch_at_startup: bool,
+    #[serde(default = "default_update_check_interval")]
+    pub update_check_interval_mins: u64,
+    #[serde(default)]
+    pub auto_update: AutoUpdatePolicy,
+    #[serde(default)]
+    pub launch: LaunchSettings,
+    /// Captive portal check endpoint, for networks that block the default one
+    #[serde(default)]
+    pub captive_check_url: Option<String>,
+}
+
+fn current_config_version() -> u32 {
+    CURRENT_CONFIG_VERSION
+}
+
+fn default_update_check_interval() -> u64 {
+    10
 }
 
 impl Default for LauncherConfig {
     fn default() -> Self {
         Self {
+            version: CURRENT_CONFIG_VERSION,
             language: "en".to_string(),
             close_behavior: CloseBehavior::MinimizeToTray,
             launch_at_startup: false,
+            update_check_interval_mins: default_update_check_interval(),
+            auto_update: AutoUpdatePolicy::default(),
+            launch: LaunchSettings::default(),
+            captive_check_url: None,
         }
     }
 }
 
+/// v1 (no version field) saved whatever language tag it was given, e.g. "en-US" or "ja"
+fn migrate_v1(obj: &mut serde_json::Map<String, Value>) {
+    if let Some(lang) = obj.get("language").and_then(|l| l.as_str()) {
+        let base = lang.split(['-', '_']).next().unwrap_or(lang).to_lowercase();
+        let lang = if base == "ja" { "jp".to_string() } else { base };
+        obj.insert("language".to_string(), Value::String(lang));
+    }
+}
+
+/// `MIGRATIONS[n]` upgrades config version n+1 to n+2
+const MIGRATIONS: &[fn(&mut serde_json::Map<String, Value>)] = &[migrate_v1];
+
+/// Upgrade a config written by an older launcher, one version at a time
+fn migrate(obj: &mut serde_json::Map<String, Value>) {
+    let mut version = obj.get("version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+    while version < CURRENT_CONFIG_VERSION {
+        if let Some(step) = MIGRATIONS.get(version.max(1) as usize - 1) {
+            step(obj);
+        }
+        version += 1;
+        obj.insert("version".to_string(), version.into());
+    }
+}
+
+/// Start from the defaults and take each saved field that still deserializes,
+/// so one bad or renamed field doesn't reset everything else
+fn from_value_lenient(obj: serde_json::Map<String, Value>) -> LauncherConfig {
+    let mut merged = match serde_json::to_value(LauncherConfig::default()) {
+        Ok(Value::Object(map)) => map,
+        _ => return LauncherConfig::default(),
+    };
+    for (key, value) in obj {
+        let previous = merged.insert(key.clone(), value);
+        if serde_json::from_value::<LauncherConfig>(Value::Object(merged.clone())).is_err() {
+            log::warn!("Ignoring invalid config value for '{}'", key);
+            match previous {
+                Some(v) => merged.insert(key, v),
+                None => merged.remove(&key),
+            };
+        }
+    }
+    serde_json::from_value(Value::Object(merged)).unwrap_or_default()
+}
+
+fn parse_config(content: &str) -> Option<LauncherConfig> {
+    let Ok(Value::Object(mut obj)) = serde_json::from_str::<Value>(content) else {
+        return None;
+    };
+    migrate(&mut obj);
+    Some(from_value_lenient(obj))
+}
+
+/// Write `<path>.tmp` and rename it over `path`, so a crash mid-write leaves the old file intact
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp_path, path)
+}
+
+fn backup_path(config_path: &Path) -> PathBuf {
+    config_path.with_extension("json.bak")
+}
+
 impl LauncherConfig {
+    /// Saved config, else the backup of the last good one, else defaults.
+    /// Individual bad fields fall back to their defaults (see `from_value_lenient`).
     pub fn load() -> Self {
-        let config_path = PathBuf::from("config.json");
-        if config_path.exists() {
-            if let Ok(content) = fs::read_to_string(config_path) {
-                if let Ok(config) = serde_json::from_str(&content) {
-                    return config;
-                }
-            }
+        // With a path override (tests) the working directory is just wherever cargo ran, not an old install
+        let legacy_dir = if paths::is_overridden() { None } else { std::env::current_dir().ok() };
+        Self::load_with_legacy(legacy_dir.as_deref())
+    }
+
+    /// `load`, looking for a pre-LauncherPaths config.json in `legacy_dir` when there's no saved config
+    pub fn load_with_legacy(legacy_dir: Option<&Path>) -> Self {
+        let config_path = paths::get_config_path();
+        let content = match fs::read_to_string(&config_path) {
+            Ok(content) => content,
+            Err(_) => return legacy_dir.and_then(Self::load_legacy).unwrap_or_default(),
+        };
+        if let Some(config) = parse_config(&content) {
+            return config;
+        }
+        log::warn!("{} is corrupt, trying the backup", config_path.display());
+        fs::read_to_string(backup_path(&config_path))
+            .ok()
+            .and_then(|backup| parse_config(&backup))
+            .unwrap_or_default()
+    }
+
+    /// Older launchers kept config.json in the working directory. Move it to the real location once.
+    fn load_legacy(dir: &Path) -> Option<Self> {
+        let legacy = dir.join("config.json");
+        if legacy == paths::get_config_path() {
+            return None;
         }
-        Self::default()
+        let config = parse_config(&fs::read_to_string(&legacy).ok()?)?;
+        log::info!("Moving settings from {}", legacy.display());
+        log_err!(config.save(), "save migrated config");
+        Some(config)
     }
 
-    pub fn save(&self) {
-        let config_path = PathBuf::from("config.json");
-        if let Ok(content) = serde_json::to_string_pretty(self) {
-            let _ = fs::write(config_path, content);
+    /// Write to a temp file and rename over config.json, so a crash mid-write can't leave it
+    /// half written. The previous config is kept as config.json.bak if it was readable.
+    pub fn save(&self) -> std::io::Result<()> {
+        let config_path = paths::get_config_path();
+        if let Some(dir) = config_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+
+        let previous_ok = fs::read_to_string(&config_path)
+            .map(|c| serde_json::from_str::<Value>(&c).is_ok())
+            .unwrap_or(false);
+        if previous_ok {
+            log_err!(fs::copy(&config_path, backup_path(&config_path)), "back up config");
+        }
+
+        write_atomic(&config_path, content.as_bytes())
+    }
+
+    /// Fix values the rest of the launcher can't use, returning what was changed.
+    /// Language and profile are only checked when we have a manifest to check them against.
+    pub fn validate(&mut self, manifest: Option<&GameManifest>) -> Vec<String> {
+        let mut fixed = Vec::new();
+
+        let languages = manifest.map(|m| m.presentation.languages.as_slice()).unwrap_or_default();
+        if !languages.is_empty() && !languages.contains(&self.language) {
+            let fallback = if languages.iter().any(|l| l == "en") { "en" } else { languages[0].as_str() };
+            fixed.push(format!("language '{}' is not offered, using '{}'", self.language, fallback));
+            self.language = fallback.to_string();
+        }
+        if self.update_check_interval_mins == 0 {
+            fixed.push("update check interval can't be 0".to_string());
+            self.update_check_interval_mins = default_update_check_interval();
+        }
+        if let Some(window) = &self.auto_update.window {
+            let valid = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").is_ok();
+            if !valid(&window.start) || !valid(&window.end) {
+                fixed.push(format!("update window {}-{} is not HH:MM", window.start, window.end));
+                self.auto_update.window = None;
+            }
+        }
+        if let (Some(m), Some(profile)) = (manifest, &self.launch.default_profile) {
+            if !launch::available_profiles(m.launch.as_ref(), &self.launch).iter().any(|p| &p.name == profile) {
+                fixed.push(format!("default profile '{}' doesn't exist", profile));
+                self.launch.default_profile = None;
+            }
+        }
+
+        for problem in &fixed {
+            log::warn!("Config: {}", problem);
         }
+        fixed
     }
 }
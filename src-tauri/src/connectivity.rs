@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::config::LauncherConfig;
+use crate::launcher_core::net::{self, CAPTIVE_CHECK_URL};
+use crate::poller;
+use crate::state::{AppState, GameStatus};
+use crate::logger::log_err;
+
+pub use crate::launcher_core::net::{classify_error, Connectivity};
+
+/// How long a connectivity observation is trusted before we probe again
+pub const CONNECTIVITY_TTL: Duration = Duration::from_secs(30);
+
+/// Last observed connectivity and when we observed it
+pub struct ConnectivityCache {
+    state: Connectivity,
+    checked_at: Option<Instant>,
+}
+
+impl ConnectivityCache {
+    pub fn new() -> Self {
+        Self {
+            state: Connectivity::Unknown,
+            checked_at: None,
+        }
+    }
+
+    pub fn current(&self) -> Connectivity {
+        self.state
+    }
+
+    /// Cached state, only if it was observed within the TTL
+    pub fn fresh(&self) -> Option<Connectivity> {
+        match self.checked_at {
+            Some(at) if at.elapsed() < CONNECTIVITY_TTL => Some(self.state),
+            _ => None,
+        }
+    }
+
+    /// Store a new observation. Returns true if the state changed.
+    pub fn update(&mut self, state: Connectivity) -> bool {
+        let changed = self.state != state;
+        self.state = state;
+        self.checked_at = Some(Instant::now());
+        changed
+    }
+}
+
+/// Record an observation. On change, set/clear GameStatus::Offline and notify the frontend.
+/// A running game or install keeps its status, only manifest-derived ones go Offline.
+pub fn report(app: &AppHandle, observed: Connectivity) {
+    let state = app.state::<AppState>();
+    let changed = state.connectivity.lock().unwrap().update(observed);
+    if !changed {
+        return;
+    }
+    log::info!("Connectivity changed to {:?}", observed);
+
+    let back_online = {
+        let mut status = state.status.lock().unwrap();
+        if observed.is_online() && *status == GameStatus::Offline {
+            *status = GameStatus::Checking;
+            true
+        } else {
+            if !observed.is_online() && status.is_derived() {
+                *status = GameStatus::Offline;
+            }
+            false
+        }
+    };
+
+    log_err!(app.emit("connectivity-changed", observed), "emit connectivity-changed");
+    if back_online {
+        // Straight back to Ready/UpdateAvailable/... from the manifest we have
+        poller::refresh_status(app);
+    }
+}
+
+/// Record a failed request to `url`, already run through `classify_error`. If the host didn't
+/// answer, probe to tell a captive portal apart from being offline.
+pub async fn report_failure(app: &AppHandle, url: &str, classified: Connectivity) -> Connectivity {
+    if classified.is_online() {
+        report(app, classified);
+        return classified;
+    }
+    check(app, url, true).await
+}
+
+/// `captive_check_url` from config.json, or the default endpoint
+pub fn check_url(config: &LauncherConfig) -> String {
+    config
+        .captive_check_url
+        .clone()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| CAPTIVE_CHECK_URL.to_string())
+}
+
+/// Probe `url` with the configured captive check endpoint
+pub async fn probe(url: &str, config: &LauncherConfig) -> Connectivity {
+    net::probe_with(url, &check_url(config)).await
+}
+
+/// Cached connectivity if still fresh, otherwise probe the manifest host
+pub async fn check(app: &AppHandle, url: &str, force: bool) -> Connectivity {
+    if !force {
+        let cached = { app.state::<AppState>().connectivity.lock().unwrap().fresh() };
+        if let Some(c) = cached {
+            return c;
+        }
+    }
+    let config = { app.state::<AppState>().config.lock().unwrap().clone() };
+    let observed = probe(url, &config).await;
+    report(app, observed);
+    observed
+}
+
+/// While we're offline, keep probing so the launcher recovers on its own
+pub fn spawn_watcher(app: AppHandle, url: &'static str) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CONNECTIVITY_TTL).await;
+            let current = { app.state::<AppState>().connectivity.lock().unwrap().current() };
+            if !current.is_online() {
+                check(&app, url, true).await;
+            }
+        }
+    });
+}
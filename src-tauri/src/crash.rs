@@ -0,0 +1,144 @@
+use chrono::Local;
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+use crate::config::LauncherConfig;
+use crate::diagnostics;
+use crate::launcher_core::{compat, game};
+use crate::paths;
+use crate::supervisor::GameExit;
+use crate::logger::log_err;
+
+/// Keep only the newest reports
+const MAX_CRASH_REPORTS: usize = 10;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CrashReport {
+    pub name: String,
+    pub path: String,
+    pub exit: GameExit,
+}
+
+#[derive(Serialize)]
+struct CrashInfo<'a> {
+    exit: &'a GameExit,
+    installed_version: String,
+    manifest_version: Option<String>,
+    launcher_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    family: &'static str,
+}
+
+pub fn get_crash_reports_dir() -> PathBuf {
+    paths::get_cache_dir().join("crash_reports")
+}
+
+/// Non-zero exit code or killed by a signal
+pub fn is_crash(exit: &GameExit) -> bool {
+    exit.signal.is_some() || matches!(exit.exit_code, Some(code) if code != 0)
+}
+
+fn add_file<W: Write + std::io::Seek>(zip: &mut ZipWriter<W>, name: &str, content: &[u8]) -> zip::result::ZipResult<()> {
+    zip.start_file(name, FileOptions::default())?;
+    zip.write_all(content)?;
+    Ok(())
+}
+
+/// Zip up everything support needs to look at a crash
+pub fn build_bundle(
+    exit: &GameExit,
+    config: &LauncherConfig,
+    manifest_version: Option<String>,
+) -> Result<CrashReport, Box<dyn std::error::Error>> {
+    let dir = get_crash_reports_dir();
+    fs::create_dir_all(&dir)?;
+    let name = format!("crash-{}.zip", Local::now().format("%Y%m%d-%H%M%S"));
+    let path = dir.join(&name);
+
+    let mut zip = ZipWriter::new(File::create(&path)?);
+
+    let info = CrashInfo {
+        exit,
+        installed_version: game::get_local_version(),
+        manifest_version,
+        launcher_version: compat::current_version(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        family: std::env::consts::FAMILY,
+    };
+    add_file(&mut zip, "crash.json", serde_json::to_string_pretty(&info)?.as_bytes())?;
+    // Same redaction as the diagnostics bundle, launch env vars and anything secret-looking stay here
+    add_file(&mut zip, "config.json", serde_json::to_string_pretty(&diagnostics::redacted_config(config))?.as_bytes())?;
+
+    if let Some(log) = &exit.log_file {
+        if let Ok(content) = fs::read(log) {
+            add_file(&mut zip, "game.log", &content)?;
+        }
+    }
+    if let Ok(content) = fs::read(paths::get_logs_dir().join("launcher.log")) {
+        add_file(&mut zip, "launcher.log", &content)?;
+    }
+
+    zip.finish()?;
+    prune_reports(&dir);
+
+    Ok(CrashReport {
+        name,
+        path: path.to_string_lossy().to_string(),
+        exit: exit.clone(),
+    })
+}
+
+fn prune_reports(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|e| e == "zip").unwrap_or(false))
+        .collect();
+    // Names carry the timestamp, newest last
+    reports.sort();
+    while reports.len() > MAX_CRASH_REPORTS {
+        let oldest = reports.remove(0);
+        log_err!(fs::remove_file(&oldest), "prune {}", oldest.display());
+    }
+}
+
+/// Resolve a report by file name, refusing anything outside crash_reports/
+pub fn report_path(name: &str) -> Result<PathBuf, String> {
+    if !name.starts_with("crash-") || !name.ends_with(".zip") || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("Invalid crash report '{}'", name));
+    }
+    let path = get_crash_reports_dir().join(name);
+    if !path.exists() {
+        return Err(format!("Crash report '{}' not found", name));
+    }
+    Ok(path)
+}
+
+/// POST the zip as-is to the endpoint from the manifest
+pub async fn upload(report: &Path, endpoint: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let body = fs::read(report)?;
+    let name = report
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let client = reqwest::Client::new();
+    client
+        .post(endpoint)
+        .header(CONTENT_TYPE, "application/zip")
+        .header("X-Crash-Report", name)
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::config::LaunchSettings;
+use crate::launcher_core::launch;
+use crate::launcher_core::manifest::GameManifest;
+
+pub const SCHEME: &str = "ganhrong";
+const MAX_LINK_LEN: usize = 2048;
+const MAX_VALUE_LEN: usize = 64;
+
+/// What a ganhrong:// link asks for
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum DeepLink {
+    /// ganhrong://launch?profile=<name>
+    Launch { profile: Option<String> },
+    /// ganhrong://join?server=<id>&profile=<name>
+    Join { server: String, profile: Option<String> },
+    /// ganhrong://news/<id>
+    News { id: String },
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum DeepLinkError {
+    #[error("Not a ganhrong:// link")]
+    WrongScheme,
+    #[error("Malformed link")]
+    Malformed,
+    #[error("Unknown link action '{0}'")]
+    UnknownAction(String),
+    #[error("Link is missing '{0}'")]
+    Missing(&'static str),
+    #[error("Unexpected link parameter '{0}'")]
+    UnexpectedParam(String),
+    #[error("Invalid value for '{0}'")]
+    InvalidValue(&'static str),
+    #[error("{0} '{1}' isn't available")]
+    NotAllowed(&'static str, String),
+}
+
+/// Names and ids only: letters, digits, space, '.', '_' and '-'. Nothing that could become a flag or a path.
+fn is_safe_value(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_VALUE_LEN
+        && !value.starts_with(['-', '.', ' '])
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '.' | '_' | '-'))
+}
+
+/// Query parameters, rejecting any the action doesn't take and any given twice
+fn params(url: &Url, allowed: &[&'static str]) -> Result<HashMap<&'static str, String>, DeepLinkError> {
+    let mut found = HashMap::new();
+    for (key, value) in url.query_pairs() {
+        let Some(&key) = allowed.iter().find(|a| **a == key) else {
+            return Err(DeepLinkError::UnexpectedParam(key.to_string()));
+        };
+        if !is_safe_value(&value) {
+            return Err(DeepLinkError::InvalidValue(key));
+        }
+        if found.insert(key, value.to_string()).is_some() {
+            return Err(DeepLinkError::InvalidValue(key));
+        }
+    }
+    Ok(found)
+}
+
+/// Parse a link into an action. Only the shape is checked here, see `validate` for the allowlist.
+pub fn parse(link: &str) -> Result<DeepLink, DeepLinkError> {
+    if link.len() > MAX_LINK_LEN {
+        return Err(DeepLinkError::Malformed);
+    }
+    let url = Url::parse(link).map_err(|_| DeepLinkError::Malformed)?;
+    if url.scheme() != SCHEME {
+        return Err(DeepLinkError::WrongScheme);
+    }
+    if !url.username().is_empty() || url.password().is_some() || url.port().is_some() || url.fragment().is_some() {
+        return Err(DeepLinkError::Malformed);
+    }
+    let action = url.host_str().unwrap_or_default().to_lowercase();
+    let segments: Vec<&str> = url.path_segments().map(|s| s.filter(|s| !s.is_empty()).collect()).unwrap_or_default();
+
+    match action.as_str() {
+        "launch" | "join" if !segments.is_empty() => Err(DeepLinkError::Malformed),
+        "launch" => {
+            let mut p = params(&url, &["profile"])?;
+            Ok(DeepLink::Launch { profile: p.remove("profile") })
+        }
+        "join" => {
+            let mut p = params(&url, &["server", "profile"])?;
+            let server = p.remove("server").ok_or(DeepLinkError::Missing("server"))?;
+            Ok(DeepLink::Join { server, profile: p.remove("profile") })
+        }
+        "news" => {
+            params(&url, &[])?;
+            let [id] = segments.as_slice() else {
+                return Err(DeepLinkError::Missing("news id"));
+            };
+            if !is_safe_value(id) {
+                return Err(DeepLinkError::InvalidValue("news id"));
+            }
+            Ok(DeepLink::News { id: id.to_string() })
+        }
+        _ => Err(DeepLinkError::UnknownAction(action)),
+    }
+}
+
+impl DeepLink {
+    /// Allowlist check: profiles, servers and news must be ones the current manifest (or the
+    /// player's own settings, for profiles) actually offers
+    pub fn validate(&self, manifest: &GameManifest, settings: &LaunchSettings) -> Result<(), DeepLinkError> {
+        if let DeepLink::Launch { profile: Some(name) } | DeepLink::Join { profile: Some(name), .. } = self {
+            let profiles = launch::available_profiles(manifest.launch.as_ref(), settings);
+            if !profiles.iter().any(|p| &p.name == name) {
+                return Err(DeepLinkError::NotAllowed("Profile", name.clone()));
+            }
+        }
+        match self {
+            DeepLink::Launch { .. } => {}
+            DeepLink::Join { server, .. } => {
+                let servers = manifest.launch.as_ref().map(|l| l.servers.as_slice()).unwrap_or_default();
+                if !servers.iter().any(|s| &s.id == server) {
+                    return Err(DeepLinkError::NotAllowed("Server", server.clone()));
+                }
+            }
+            DeepLink::News { id } => {
+                if !manifest.presentation.news.iter().any(|n| n.id.as_ref() == Some(id)) {
+                    return Err(DeepLinkError::NotAllowed("News", id.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The link a browser passed on our command line, if any
+pub fn find_in_args(args: &[String]) -> Option<&str> {
+    let prefix = format!("{}:", SCHEME);
+    args.iter().skip(1).find(|a| a.starts_with(&prefix)).map(|a| a.as_str())
+}
+
+/// Register as the ganhrong:// handler through a .desktop entry in ~/.local/share/applications.
+/// Only touches anything when the entry is missing or points at another executable.
+#[cfg(target_os = "linux")]
+pub fn register() -> std::io::Result<()> {
+    use std::path::PathBuf;
+
+    let Some(data) = std::env::var_os("XDG_DATA_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))
+    else {
+        return Ok(());
+    };
+    let exe = crate::autostart::launcher_exe()?;
+
+    let file_name = "ganhrong-launcher-url.desktop";
+    let dir = data.join("applications");
+    let path = dir.join(file_name);
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=GanhRong Launcher\nExec={} %u\nTerminal=false\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        crate::autostart::exec_quote(&exe.to_string_lossy()),
+        SCHEME
+    );
+    if std::fs::read_to_string(&path).is_ok_and(|current| current == entry) {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(&path, entry)?;
+    log::info!("Registered {}:// links via {}", SCHEME, path.display());
+    let status = std::process::Command::new("xdg-mime")
+        .args(["default", file_name, &format!("x-scheme-handler/{}", SCHEME)])
+        .status();
+    if !status.is_ok_and(|s| s.success()) {
+        log::warn!("xdg-mime failed, {}:// links may not open the launcher", SCHEME);
+    }
+    Ok(())
+}
+
+/// No registration on Windows and macOS yet (registry key / Info.plist)
+#[cfg(not(target_os = "linux"))]
+pub fn register() -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+use crate::config::LauncherConfig;
+use crate::connectivity::{self, Connectivity};
+use crate::launcher_core::{compat, download, game};
+use crate::launcher_core::download::FileProblem;
+use crate::launcher_core::manifest::{self, GameManifest};
+use crate::paths;
+
+/// Config keys whose values never leave the machine
+const SECRET_KEYS: &[&str] = &["token", "secret", "password", "key", "auth"];
+/// Launch arguments can carry tokens or server passwords too (`-token=...`), so they go as well
+const ARG_KEYS: &[&str] = &["env", "args", "extra_args"];
+
+/// Installed files checked against the release's file list
+#[derive(Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+enum FileCheck {
+    NotInstalled,
+    NoFileList,
+    /// The file list is for `latest`, which isn't what's installed
+    NotLatestVersion { latest: String },
+    FetchFailed { error: String },
+    Checked { files: usize, missing: usize, size_mismatch: usize, hash_mismatch: usize, unreadable: usize },
+}
+
+#[derive(Serialize)]
+struct InstallSummary {
+    installed_version: String,
+    game_dir_exists: bool,
+    executable: Option<String>,
+    executable_exists: bool,
+    files: FileCheck,
+}
+
+#[derive(Serialize)]
+struct DiskSpace {
+    path: String,
+    available_bytes: Option<u64>,
+    total_bytes: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct HostCheck {
+    url: String,
+    result: Connectivity,
+    millis: u128,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    launcher_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    generated_at: String,
+    paths: paths::LauncherPaths,
+    install: InstallSummary,
+    disk: DiskSpace,
+    connectivity: Vec<HostCheck>,
+}
+
+/// Replace values of secret-looking keys, and all launch env values and args (may hold tokens)
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let lower = key.to_lowercase();
+                if SECRET_KEYS.iter().any(|s| lower.contains(s)) || ARG_KEYS.contains(&lower.as_str()) {
+                    redact_all(v);
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn redact_all(value: &mut Value) {
+    match value {
+        Value::Object(map) => map.values_mut().for_each(redact_all),
+        Value::Array(items) => items.iter_mut().for_each(redact_all),
+        Value::Null => {}
+        other => *other = Value::String("<redacted>".to_string()),
+    }
+}
+
+pub fn redacted_config(config: &LauncherConfig) -> Value {
+    let mut value = serde_json::to_value(config).unwrap_or(Value::Null);
+    redact(&mut value);
+    value
+}
+
+/// Same size and hash check as `--headless verify`, summarised as counts
+async fn check_files(manifest: Option<&GameManifest>, installed: &str, game_dir: &Path) -> FileCheck {
+    if installed == "0.0.0" {
+        return FileCheck::NotInstalled;
+    }
+    let Some((m, url)) = manifest.and_then(|m| m.file_list.as_ref().map(|url| (m, url))) else {
+        return FileCheck::NoFileList;
+    };
+    if m.latest_version != installed {
+        return FileCheck::NotLatestVersion { latest: m.latest_version.clone() };
+    }
+    let list = match manifest::fetch_file_list(url).await {
+        Ok(list) => list,
+        Err(e) => return FileCheck::FetchFailed { error: e.to_string() },
+    };
+    let bad = download::check_files(game_dir, &list);
+    let count = |problem: FileProblem| bad.iter().filter(|(_, p)| *p == problem).count();
+    FileCheck::Checked {
+        files: list.files.len(),
+        missing: count(FileProblem::Missing),
+        size_mismatch: count(FileProblem::SizeMismatch),
+        hash_mismatch: count(FileProblem::HashMismatch),
+        unreadable: count(FileProblem::Unreadable),
+    }
+}
+
+async fn install_summary(manifest: Option<&GameManifest>) -> InstallSummary {
+    let game_dir = paths::get_game_dir();
+    let installed_version = game::get_local_version();
+    let exe = manifest.map(|m| m.game_exe.clone().unwrap_or_else(|| "game.exe".to_string()));
+    InstallSummary {
+        files: check_files(manifest, &installed_version, &game_dir).await,
+        installed_version,
+        game_dir_exists: game_dir.exists(),
+        executable_exists: exe.as_ref().map(|e| game_dir.join(e).exists()).unwrap_or(false),
+        executable: exe,
+    }
+}
+
+fn disk_space() -> DiskSpace {
+    let dir = paths::get_data_dir();
+    DiskSpace {
+        path: dir.to_string_lossy().to_string(),
+        available_bytes: fs2::available_space(&dir).ok(),
+        total_bytes: fs2::total_space(&dir).ok(),
+    }
+}
+
+async fn check_host(url: &str, config: &LauncherConfig) -> HostCheck {
+    let started = Instant::now();
+    let result = connectivity::probe(url, config).await;
+    HostCheck {
+        url: url.to_string(),
+        result,
+        millis: started.elapsed().as_millis(),
+    }
+}
+
+fn add_file<W: Write + Seek>(zip: &mut ZipWriter<W>, name: &str, content: &[u8]) -> zip::result::ZipResult<()> {
+    zip.start_file(name, FileOptions::default())?;
+    zip.write_all(content)?;
+    Ok(())
+}
+
+fn add_if_exists<W: Write + Seek>(zip: &mut ZipWriter<W>, name: &str, path: &Path) -> zip::result::ZipResult<()> {
+    match fs::read(path) {
+        Ok(content) => add_file(zip, name, &content),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Everything support asks for, in one zip at `dest`
+pub async fn export(
+    dest: PathBuf,
+    config: LauncherConfig,
+    manifest: Option<GameManifest>,
+    manifest_url: &str,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut hosts = vec![check_host(manifest_url, &config).await];
+    if let Some(m) = &manifest {
+        hosts.push(check_host(&m.game_zip, &config).await);
+    }
+
+    let summary = Summary {
+        launcher_version: compat::current_version(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        generated_at: chrono::Local::now().to_rfc3339(),
+        paths: paths::current(),
+        install: install_summary(manifest.as_ref()).await,
+        disk: disk_space(),
+        connectivity: hosts,
+    };
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(&dest)?);
+    add_file(&mut zip, "summary.json", serde_json::to_string_pretty(&summary)?.as_bytes())?;
+    add_file(&mut zip, "config.json", serde_json::to_string_pretty(&redacted_config(&config))?.as_bytes())?;
+    add_if_exists(&mut zip, "manifest.json", &paths::get_cache_dir().join("manifest.json"))?;
+    add_if_exists(&mut zip, "version.txt", &paths::get_game_dir().join("version.txt"))?;
+
+    if let Ok(entries) = fs::read_dir(paths::get_logs_dir()) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("launcher") && name.ends_with(".log") {
+                add_if_exists(&mut zip, &format!("logs/{}", name), &entry.path())?;
+            }
+        }
+    }
+
+    zip.finish()?;
+    Ok(dest)
+}
@@ -1,181 +1,97 @@
-use futures_util::StreamExt;
-use reqwest::Client;
-use std::cmp::min;
-use std::fs::{self, File};
-use std::io::{self, BufReader, Write};
-use std::path::Path;
-use std::time::Instant;
-use zip::ZipArchive;
-use sha2::{Sha256, Digest};
-use tauri::{AppHandle, Emitter};
-use crate::paths;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::launcher_core::download;
+use crate::launcher_core::manifest::GameManifest;
+use crate::launcher_core::progress::{DownloadProgress, ProgressSink};
+use crate::state::{AppState, GameStatus};
+use crate::logger::log_err;
 
-// Event for frontend progress
-#[derive(Clone, serde::Serialize)]
-struct DownloadProgressPayload {
-    progress: f32,        // 0.0 to 100.0
-    status: String,
-    downloaded: u64,      // bytes downloaded
-    total: u64,           // total bytes
-    speed: f64,           // bytes per second
-}
-
-pub async fn download_and_install_game<F>(
-    url: &str, 
-    checksum: &str,
-    version: &str,
+/// Forwards core progress reports to the frontend as `event`
+pub struct TauriProgress {
     app: AppHandle,
-    progress_callback: F
-) -> Result<(), Box<dyn std::error::Error>> 
-where F: Fn(f32, String) + Send + Sync + 'static 
-{
-    let cache_dir = paths::get_cache_dir();
-    let game_dir = paths::get_game_dir();
-    
-    let _ = fs::create_dir_all(&cache_dir);
-    let target_path = cache_dir.join("game.tmp.zip");
-    
-    // 1. Download
-    progress_callback(0.0, "Downloading...".to_string());
-    
-    let client = Client::new();
-    let res = client.get(url).send().await?;
-    let total_size = res.content_length().unwrap_or(0);
-    
-    let mut file = File::create(&target_path)?;
-    let mut downloaded: u64 = 0;
-    let mut stream = res.bytes_stream();
-    
-    // Speed calculation
-    let _start_time = Instant::now();
-    let mut last_update = Instant::now();
-    let mut last_downloaded: u64 = 0;
-    let mut current_speed: f64 = 0.0;
+    event: &'static str,
+}
 
-    while let Some(item) = stream.next().await {
-        let chunk = item?;
-        file.write_all(&chunk)?;
-        downloaded = min(downloaded + (chunk.len() as u64), total_size);
-        
-        // Calculate speed every 500ms
-        let now = Instant::now();
-        let elapsed = now.duration_since(last_update).as_secs_f64();
-        if elapsed >= 0.5 {
-            let bytes_since = downloaded - last_downloaded;
-            current_speed = bytes_since as f64 / elapsed;
-            last_downloaded = downloaded;
-            last_update = now;
-        }
-        
-        if total_size > 0 {
-            let p = (downloaded as f32 / total_size as f32) * 100.0;
-            app.emit("download-progress", DownloadProgressPayload { 
-                progress: p, 
-                status: "Downloading".to_string(),
-                downloaded,
-                total: total_size,
-                speed: current_speed,
-            })?;
-        }
+impl TauriProgress {
+    pub fn new(app: &AppHandle, event: &'static str) -> Self {
+        Self { app: app.clone(), event }
     }
+}
 
-    // 2. Verify
-    progress_callback(100.0, "Verifying...".to_string());
-    app.emit("download-progress", DownloadProgressPayload { 
-        progress: 100.0, 
-        status: "Verifying".to_string(),
-        downloaded: total_size,
-        total: total_size,
-        speed: 0.0,
-    })?;
-    
-    if !verify_hash(&target_path, checksum)? {
-        return Err("Phiên bản tải về bị lỗi. Vui lòng tải lại.".into());
+impl ProgressSink for TauriProgress {
+    fn progress(&self, progress: DownloadProgress) {
+        log_err!(self.app.emit(self.event, progress), "emit progress");
     }
+}
 
-    // 3. Extract (Atomic-ish)
-    progress_callback(100.0, "Installing...".to_string());
-    app.emit("download-progress", DownloadProgressPayload { 
-        progress: 100.0, 
-        status: "Installing".to_string(),
-        downloaded: total_size,
-        total: total_size,
-        speed: 0.0,
-    })?;
-    
-    let extract_path = cache_dir.join("extracted_tmp");
-    if extract_path.exists() {
-        fs::remove_dir_all(&extract_path)?;
-    }
-    fs::create_dir_all(&extract_path)?;
+pub const BUSY_ERROR: &str = "A download is already in progress";
 
-    extract_zip(&target_path, &extract_path)?;
+/// Something a download or install writes to
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Target {
+    /// cache/game.tmp-<version>.zip and the staged archive it becomes
+    Archive(String),
+    /// game/, swapped by installs
+    GameDir,
+}
 
-    // Write version.txt BEFORE moving
-    fs::write(extract_path.join("version.txt"), version)?;
+/// What an install of `version` writes: its archive, then game/
+pub fn install_targets(version: &str) -> [Target; 2] {
+    [Target::Archive(version.to_string()), Target::GameDir]
+}
 
-    // 4. Move to game/
-    if game_dir.exists() {
-        fs::remove_dir_all(&game_dir)?;
+/// Claim all of `targets`, false (claiming nothing) if any is taken. Jobs on different targets run
+/// side by side, so staging a preload doesn't hold up installing the current version.
+/// Whoever gets them must call `end` with the same targets when done.
+pub fn try_begin(app: &AppHandle, targets: &[Target]) -> bool {
+    let state = app.state::<AppState>();
+    let mut busy = state.download_busy.lock().unwrap();
+    if targets.iter().any(|t| busy.contains(t)) {
+        return false;
     }
-    
-    // Rename/Move
-    // Note: rename only works on same filesystem. 
-    // Since cache and game are likely on same drive, this is usually atomic or fast.
-    fs::rename(&extract_path, &game_dir)?;
-    
-    // Write installed version provided by manifest logic elsewhere, 
-    // but here we just ensure the files are there.
-    // The main logic will write version.txt after this succeeds.
-
-    // Cleanup
-    let _ = fs::remove_file(target_path);
-
-    Ok(())
+    busy.extend(targets.iter().cloned());
+    true
 }
 
-fn verify_hash(path: &Path, expected_hash_prefix: &str) -> Result<bool, std::io::Error> {
-    let mut file = File::open(path)?;
-    let mut hasher = Sha256::new();
-    io::copy(&mut file, &mut hasher)?;
-    let hash = hasher.finalize();
-    let hash_str = hex::encode(hash);
-    
-    // Compare prefix (e.g. "sha256:XXXX" or just "XXXX")
-    // If manifest has "sha256:", strip it.
-    let expected = if expected_hash_prefix.starts_with("sha256:") {
-        &expected_hash_prefix[7..]
-    } else {
-        expected_hash_prefix
-    };
-
-    // Case-insensitive comparison
-    Ok(hash_str.to_lowercase() == expected.to_lowercase())
+pub fn end(app: &AppHandle, targets: &[Target]) {
+    let state = app.state::<AppState>();
+    let mut busy = state.download_busy.lock().unwrap();
+    for target in targets {
+        busy.remove(target);
+    }
 }
 
-fn extract_zip(zip_path: &Path, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::open(zip_path)?;
-    let reader = BufReader::new(file);
-    let mut archive = ZipArchive::new(reader)?;
-
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let outpath = match file.enclosed_name() {
-            Some(path) => target_dir.join(path),
-            None => continue,
-        };
+/// Run download + install for the manifest's latest version in the background.
+/// Fails with `BUSY_ERROR` if another install, or a download of the same version, is running.
+pub fn spawn_install(app: AppHandle, m: GameManifest) -> Result<(), String> {
+    let targets = install_targets(&m.latest_version);
+    if !try_begin(&app, &targets) {
+        return Err(BUSY_ERROR.to_string());
+    }
+    *app.state::<AppState>().status.lock().unwrap() = GameStatus::Downloading(0.0);
 
-        if file.name().ends_with('/') {
-            fs::create_dir_all(&outpath)?;
-        } else {
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    fs::create_dir_all(p)?;
-                }
+    // Spawn download task
+    tauri::async_runtime::spawn(async move {
+        let res = download::download_and_install_game(
+            &m.game_zip, 
+            &m.checksum, 
+            &m.latest_version,
+            &TauriProgress::new(&app, "download-progress"),
+        ).await;
+        end(&app, &targets);
+        
+        let state = app.state::<AppState>();
+        match res {
+            Ok(_) => {
+                log::info!("Installed {}", m.latest_version);
+                *state.status.lock().unwrap() = GameStatus::ReadyToPlay;
+                log_err!(app.emit("download-complete", ()), "emit download-complete");
+            },
+            Err(e) => {
+                log::error!("Install of {} failed: {}", m.latest_version, e);
+                *state.status.lock().unwrap() = GameStatus::UpdateAvailable; // Reset or Error state
+                log_err!(app.emit("download-error", e.to_string()), "emit download-error");
             }
-            let mut outfile = File::create(&outpath)?;
-            io::copy(&mut file, &mut outfile)?;
         }
-    }
+    });
     Ok(())
 }
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::paths;
+use crate::logger::log_err;
+
+/// Retention limits across all game logs, applied when a session starts
+const MAX_LOG_FILES: usize = 20;
+const MAX_TOTAL_SIZE: u64 = 100 * 1024 * 1024;
+/// Cap on one session's log while the launcher is around to rotate it
+const MAX_SESSION_LOG_SIZE: u64 = 20 * 1024 * 1024;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GameLogInfo {
+    pub name: String,
+    pub size: u64,
+    pub modified: String, // RFC 3339
+}
+
+fn is_game_log(name: &str) -> bool {
+    name.starts_with("game-") && name.ends_with(".log")
+}
+
+/// Drop the oldest logs until we're inside the retention limits
+fn prune_logs(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut logs: Vec<(PathBuf, u64, SystemTime)> = entries
+        .flatten()
+        .filter(|e| is_game_log(&e.file_name().to_string_lossy()))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((e.path(), meta.len(), meta.modified().ok()?))
+        })
+        .collect();
+    // Newest first
+    logs.sort_by_key(|l| std::cmp::Reverse(l.2));
+
+    let mut total = 0;
+    for (i, (path, size, _)) in logs.iter().enumerate() {
+        total += size;
+        if i >= MAX_LOG_FILES || total > MAX_TOTAL_SIZE {
+            log_err!(fs::remove_file(path), "prune {}", path.display());
+        }
+    }
+}
+
+/// New game-<timestamp>.log for one play session. The game gets the file itself as stdout/stderr
+/// rather than a pipe to us, so it keeps running fine if the launcher quits or restarts.
+/// It's opened in append mode so `rotate_if_full` can truncate it under the game.
+pub fn create_session_log() -> io::Result<(File, PathBuf)> {
+    let dir = paths::get_logs_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("game-{}.log", Local::now().format("%Y%m%d-%H%M%S")));
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    prune_logs(&dir);
+    Ok((file, path))
+}
+
+/// Once a running session's log passes MAX_SESSION_LOG_SIZE, copy it to game-<timestamp>.prev.log
+/// (replacing the previous copy) and truncate it. The game writes in append mode and carries on at
+/// the new end. Output written between the copy and the truncate is lost, and nothing rotates the
+/// log once the launcher has quit. Returns whether it rotated.
+pub fn rotate_if_full(path: &Path) -> io::Result<bool> {
+    if fs::metadata(path)?.len() <= MAX_SESSION_LOG_SIZE {
+        return Ok(false);
+    }
+    fs::copy(path, path.with_extension("prev.log"))?;
+    OpenOptions::new().write(true).open(path)?.set_len(0)?;
+    Ok(true)
+}
+
+pub fn list_game_logs() -> Vec<GameLogInfo> {
+    let Ok(entries) = fs::read_dir(paths::get_logs_dir()) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(SystemTime, GameLogInfo)> = entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if !is_game_log(&name) {
+                return None;
+            }
+            let meta = e.metadata().ok()?;
+            let modified = meta.modified().ok()?;
+            let local: DateTime<Local> = modified.into();
+            Some((modified, GameLogInfo { name, size: meta.len(), modified: local.to_rfc3339() }))
+        })
+        .collect();
+    // Newest first
+    logs.sort_by_key(|l| std::cmp::Reverse(l.0));
+    logs.into_iter().map(|(_, info)| info).collect()
+}
+
+/// Read a log by file name. Only names from `list_game_logs` are accepted.
+/// With `max_bytes`, only the tail of the file is returned.
+pub fn read_game_log(name: &str, max_bytes: Option<u64>) -> Result<String, String> {
+    if !is_game_log(name) || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("Invalid log name '{}'", name));
+    }
+    read_tail(&paths::get_logs_dir().join(name), max_bytes).map_err(|e| e.to_string())
+}
+
+fn read_tail(path: &Path, max_bytes: Option<u64>) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if let Some(max) = max_bytes {
+        if len > max {
+            file.seek(SeekFrom::Start(len - max))?;
+        }
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
@@ -0,0 +1,262 @@
+use serde::Serialize;
+use crate::config::LauncherConfig;
+use crate::launcher_core::compat;
+use crate::launcher_core::download;
+use crate::launcher_core::game::{self, GameError};
+use crate::launcher_core::launch;
+use crate::launcher_core::manifest::{self, GameManifest};
+use crate::launcher_core::progress::CliProgress;
+use crate::paths;
+use crate::poller;
+use crate::single_instance::{self, Instance, InstanceLock};
+use crate::state::GameStatus;
+
+pub const USAGE: &str = "Usage: launcher --headless <install|update|verify|launch|status> [--manifest-url <url>] [--profile <name>]";
+
+// Exit codes, stable so CI scripts can branch on them
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILED: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_NO_MANIFEST: i32 = 3;    // Offline and nothing cached
+pub const EXIT_NOT_INSTALLED: i32 = 4;
+pub const EXIT_OUT_OF_DATE: i32 = 5;    // Installed version isn't the latest
+pub const EXIT_VERIFY_FAILED: i32 = 6;
+pub const EXIT_LAUNCHER_OUTDATED: i32 = 7;
+pub const EXIT_GAME_RUNNING: i32 = 8;
+pub const EXIT_GAME_FAILED: i32 = 9;    // Game exited non-zero or was killed, its code is in the JSON report
+pub const EXIT_LAUNCHER_RUNNING: i32 = 10;  // install/update while the launcher window is open
+
+#[derive(Serialize)]
+struct StatusReport {
+    status: GameStatus,
+    local_version: String,
+    latest_version: String,
+    server_status: Option<String>,
+    manifest_cached: bool, // True if we couldn't reach the manifest host
+}
+
+/// Printed when a headless launch ends
+#[derive(Serialize)]
+struct LaunchReport {
+    exit_code: Option<i32>, // None if the game was killed by a signal
+    success: bool,
+}
+
+/// install/update write game/ and cache/, so they can't run next to an open launcher doing the same.
+/// The lock is held until the returned guard drops.
+fn take_instance_lock() -> Result<Option<InstanceLock>, i32> {
+    match single_instance::acquire() {
+        Ok(Instance::Primary(lock)) => Ok(Some(lock)),
+        Ok(Instance::Secondary) => {
+            eprintln!("The launcher is running, close it first");
+            Err(EXIT_LAUNCHER_RUNNING)
+        }
+        Err(e) => {
+            eprintln!("Single instance check failed, continuing: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+/// Fresh manifest, or the cached one if the host is unreachable
+async fn load_manifest(url: &str) -> Result<(GameManifest, bool), i32> {
+    match manifest::fetch_manifest(url).await {
+        Ok(m) => Ok((m, false)),
+        Err(e) => {
+            eprintln!("Manifest fetch failed: {}", e);
+            if e.is::<manifest::UnsupportedManifest>() {
+                return Err(EXIT_LAUNCHER_OUTDATED);
+            }
+            match manifest::load_cached_manifest() {
+                Some(m) => {
+                    eprintln!("Using cached manifest");
+                    Ok((m, true))
+                }
+                None => Err(EXIT_NO_MANIFEST),
+            }
+        }
+    }
+}
+
+async fn install(m: &GameManifest, require_installed: bool) -> i32 {
+    let local = game::get_local_version();
+    if require_installed && local == "0.0.0" {
+        eprintln!("Game is not installed, use 'install'");
+        return EXIT_NOT_INSTALLED;
+    }
+    if local == m.latest_version {
+        println!("Already up to date ({})", local);
+        return EXIT_OK;
+    }
+    if let Some(running) = game::find_running_instance() {
+        eprintln!("{}", GameError::UpdateBlocked(running));
+        return EXIT_GAME_RUNNING;
+    }
+
+    println!("Installing {} (installed: {})", m.latest_version, local);
+    match download::download_and_install_game(&m.game_zip, &m.checksum, &m.latest_version, &CliProgress::default()).await {
+        Ok(()) => {
+            println!("Installed {}", m.latest_version);
+            EXIT_OK
+        }
+        Err(e) => {
+            eprintln!("Install failed: {}", e);
+            EXIT_FAILED
+        }
+    }
+}
+
+/// Check the installed version and, if the release published a file list, every file's hash
+async fn verify(m: &GameManifest) -> i32 {
+    let local = game::get_local_version();
+    if local == "0.0.0" {
+        eprintln!("Game is not installed");
+        return EXIT_NOT_INSTALLED;
+    }
+    if local != m.latest_version {
+        eprintln!("Installed {} but latest is {}", local, m.latest_version);
+        return EXIT_OUT_OF_DATE;
+    }
+
+    let game_dir = paths::get_game_dir();
+    let exe = m.game_exe.clone().unwrap_or_else(|| "game.exe".to_string());
+    if !game_dir.join(&exe).exists() {
+        eprintln!("{}", GameError::ExecutableMissing(game_dir.join(&exe)));
+        return EXIT_VERIFY_FAILED;
+    }
+
+    let Some(url) = &m.file_list else {
+        println!("Version {} installed, no file list published to check files against", local);
+        return EXIT_OK;
+    };
+    let list = match manifest::fetch_file_list(url).await {
+        Ok(list) => list,
+        Err(e) => {
+            eprintln!("Failed to fetch file list: {}", e);
+            return EXIT_FAILED;
+        }
+    };
+
+    let bad = download::check_files(&game_dir, &list);
+    for (path, problem) in &bad {
+        println!("{}: {}", path, problem);
+    }
+    println!("Verified {} files, {} bad", list.files.len(), bad.len());
+    if bad.is_empty() { EXIT_OK } else { EXIT_VERIFY_FAILED }
+}
+
+/// Start the game and wait for it. The game's own exit code goes in the JSON report rather than
+/// our exit code, so it can't be mistaken for one of ours.
+fn launch(m: &GameManifest, profile: Option<&str>) -> i32 {
+    let local = game::get_local_version();
+    if local == "0.0.0" {
+        eprintln!("Game is not installed");
+        return EXIT_NOT_INSTALLED;
+    }
+    if local != m.latest_version {
+        eprintln!("Version mismatch. Please update. (installed {}, latest {})", local, m.latest_version);
+        return EXIT_OUT_OF_DATE;
+    }
+    if let Some(running) = game::find_running_instance() {
+        eprintln!("{}", GameError::AlreadyRunning(running));
+        return EXIT_GAME_RUNNING;
+    }
+
+    let settings = LauncherConfig::load().launch;
+    let exe = m.game_exe.clone().unwrap_or_else(|| "game.exe".to_string());
+    let result = launch::build_plan(m.launch.as_ref(), &settings, profile, None, &paths::get_game_dir())
+        .map_err(GameError::from)
+        .and_then(|plan| game::launch_game(&exe, &plan, None));
+    let mut child = match result {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILED;
+        }
+    };
+
+    println!("Game started (pid {})", child.id());
+    match child.wait() {
+        Ok(status) => {
+            let report = LaunchReport { exit_code: status.code(), success: status.success() };
+            println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+            if status.success() { EXIT_OK } else { EXIT_GAME_FAILED }
+        }
+        Err(e) => {
+            eprintln!("Failed to wait for the game: {}", e);
+            EXIT_FAILED
+        }
+    }
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(|s| s.as_str())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+    Install,
+    Update,
+    Verify,
+    Launch,
+    Status,
+}
+
+impl Command {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "install" => Some(Command::Install),
+            "update" => Some(Command::Update),
+            "verify" => Some(Command::Verify),
+            "launch" => Some(Command::Launch),
+            "status" => Some(Command::Status),
+            _ => None,
+        }
+    }
+}
+
+/// `--headless <command>`: same manifest/download/install/launch logic as the UI, without a webview
+pub async fn run(command: &str, args: &[String], default_manifest_url: &str) -> i32 {
+    let Some(command) = Command::parse(command) else {
+        eprintln!("{}", USAGE);
+        return EXIT_USAGE;
+    };
+    let _lock = if matches!(command, Command::Install | Command::Update) {
+        match take_instance_lock() {
+            Ok(lock) => lock,
+            Err(code) => return code,
+        }
+    } else {
+        None
+    };
+    let url = arg_value(args, "--manifest-url").unwrap_or(default_manifest_url);
+    let (m, cached) = match load_manifest(url).await {
+        Ok(loaded) => loaded,
+        Err(code) => return code,
+    };
+    // status still reports, with LauncherOutdated as its status
+    if command != Command::Status {
+        if let Err(e) = compat::check(&m) {
+            eprintln!("{}", e);
+            return EXIT_LAUNCHER_OUTDATED;
+        }
+    }
+
+    match command {
+        Command::Status => {
+            let report = StatusReport {
+                status: poller::compute_status(&m, &game::get_local_version()),
+                local_version: game::get_local_version(),
+                latest_version: m.latest_version.clone(),
+                server_status: m.server_status.clone(),
+                manifest_cached: cached,
+            };
+            println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+            EXIT_OK
+        }
+        Command::Install => install(&m, false).await,
+        Command::Update => install(&m, true).await,
+        Command::Verify => verify(&m).await,
+        Command::Launch => launch(&m, arg_value(args, "--profile")),
+    }
+}
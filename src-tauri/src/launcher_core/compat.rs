@@ -0,0 +1,47 @@
+use semver::Version;
+use std::sync::OnceLock;
+use crate::launcher_core::manifest::GameManifest;
+
+static CURRENT_VERSION: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, thiserror::Error)]
+#[error("This launcher ({current}) is too old, version {required} or newer is required")]
+pub struct LauncherTooOld {
+    pub current: String,
+    pub required: String,
+}
+
+/// Record the running launcher's version (from tauri.conf.json). Falls back to the crate version.
+pub fn set_current_version(version: String) {
+    let _ = CURRENT_VERSION.set(version);
+}
+
+pub fn current_version() -> &'static str {
+    CURRENT_VERSION.get().map(|v| v.as_str()).unwrap_or(env!("CARGO_PKG_VERSION"))
+}
+
+/// True if `required` is newer than us. Unparseable versions are logged and ignored.
+pub fn is_too_old(required: &str) -> bool {
+    match (Version::parse(current_version()), Version::parse(required.trim_start_matches('v'))) {
+        (Ok(current), Ok(required)) => current < required,
+        _ => {
+            log::warn!("Can't compare launcher version {} against {}", current_version(), required);
+            false
+        }
+    }
+}
+
+pub fn is_outdated(manifest: &GameManifest) -> bool {
+    manifest.min_launcher_version.as_deref().map(is_too_old).unwrap_or(false)
+}
+
+/// Err if the manifest requires a newer launcher than this one
+pub fn check(manifest: &GameManifest) -> Result<(), LauncherTooOld> {
+    match &manifest.min_launcher_version {
+        Some(required) if is_too_old(required) => Err(LauncherTooOld {
+            current: current_version().to_string(),
+            required: required.clone(),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,344 @@
+use futures_util::StreamExt;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use std::cmp::min;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use zip::ZipArchive;
+use serde::Serialize;
+use sha2::{Sha256, Digest};
+use crate::launcher_core::game::{self, GameError};
+use crate::launcher_core::manifest::FileList;
+use crate::launcher_core::progress::{DownloadProgress, ProgressSink};
+use crate::paths;
+use crate::logger::log_err;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Where a verified archive for `version` waits until it gets installed
+pub fn staged_archive_path(version: &str) -> PathBuf {
+    paths::get_cache_dir().join(format!("game-{}.zip", version))
+}
+
+/// Records which checksum a staged archive was verified against, so we don't re-hash it every poll
+fn staged_marker_path(version: &str) -> PathBuf {
+    paths::get_cache_dir().join(format!("game-{}.zip.verified", version))
+}
+
+/// Set on archives staged as a preload, which get installed on their own once released
+fn preload_marker_path(version: &str) -> PathBuf {
+    paths::get_cache_dir().join(format!("game-{}.zip.preload", version))
+}
+
+/// True if a verified archive for this version is already sitting in cache/
+pub fn is_staged(version: &str, checksum: &str) -> bool {
+    let marker = fs::read_to_string(staged_marker_path(version)).unwrap_or_default();
+    staged_archive_path(version).exists() && marker.trim() == normalize_checksum(checksum)
+}
+
+pub fn mark_preloaded(version: &str) -> io::Result<()> {
+    fs::write(preload_marker_path(version), b"")
+}
+
+/// Staged, and staged as a preload rather than by the "download only" auto-update mode
+pub fn is_preloaded(version: &str, checksum: &str) -> bool {
+    is_staged(version, checksum) && preload_marker_path(version).exists()
+}
+
+/// Remove staged archives, their markers and partial downloads for any version not in `keep`
+pub fn prune_staged(keep: &[&str]) {
+    let Ok(entries) = fs::read_dir(paths::get_cache_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(rest) = name.strip_prefix("game-").or_else(|| name.strip_prefix("game.tmp-")) else {
+            continue;
+        };
+        let version = rest.trim_end_matches(".verified").trim_end_matches(".preload").trim_end_matches(".zip");
+        if !keep.contains(&version) {
+            log_err!(fs::remove_file(entry.path()), "prune {}", name);
+        }
+    }
+}
+
+/// Download, verify and install
+pub async fn download_and_install_game(
+    url: &str, 
+    checksum: &str,
+    version: &str,
+    sink: &dyn ProgressSink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // 1-2. Download + verify (skipped if already staged)
+    let archive = download_archive(url, checksum, version, sink).await?;
+
+    // 3-4. Extract + swap
+    install_archive(&archive, version, sink)?;
+
+    Ok(())
+}
+
+/// Download and verify the archive into cache/ without touching game/.
+/// Background staging reports to its own sink so it doesn't drive the main progress bar.
+pub async fn download_archive(
+    url: &str,
+    checksum: &str,
+    version: &str,
+    sink: &dyn ProgressSink,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let cache_dir = paths::get_cache_dir();
+    fs::create_dir_all(&cache_dir)?;
+
+    let staged_path = staged_archive_path(version);
+    if is_staged(version, checksum) {
+        log::info!("Using staged archive for {}", version);
+        return Ok(staged_path);
+    }
+    log::info!("Downloading {} from {}", version, url);
+
+    // Per-version temp file, so a partial download is only ever resumed for the same version.
+    // Callers run one download at a time, two writers on the same file would corrupt it.
+    let target_path = cache_dir.join(format!("game.tmp-{}.zip", version));
+    
+    // 1. Download, picking up where an interrupted attempt left off
+    let client = Client::new();
+    let (res, resume_from) = request_archive(&client, url, &target_path).await?;
+    let total_size = res.content_length().unwrap_or(0) + resume_from;
+    if resume_from > 0 {
+        log::info!("Resuming {} at {} bytes", version, resume_from);
+    }
+    
+    let mut file = if resume_from > 0 {
+        OpenOptions::new().append(true).open(&target_path)?
+    } else {
+        File::create(&target_path)?
+    };
+    let mut downloaded: u64 = resume_from;
+    let mut stream = res.bytes_stream();
+    
+    // Speed calculation
+    let _start_time = Instant::now();
+    let mut last_update = Instant::now();
+    let mut last_downloaded: u64 = resume_from; // Bytes already on disk don't count towards the speed
+    let mut current_speed: f64 = 0.0;
+
+    while let Some(item) = stream.next().await {
+        let chunk = item?;
+        file.write_all(&chunk)?;
+        downloaded = min(downloaded + (chunk.len() as u64), total_size);
+        
+        // Calculate speed every 500ms
+        let now = Instant::now();
+        let elapsed = now.duration_since(last_update).as_secs_f64();
+        if elapsed >= 0.5 {
+            let bytes_since = downloaded - last_downloaded;
+            current_speed = bytes_since as f64 / elapsed;
+            last_downloaded = downloaded;
+            last_update = now;
+        }
+        
+        if total_size > 0 {
+            let p = (downloaded as f32 / total_size as f32) * 100.0;
+            sink.progress(DownloadProgress { 
+                progress: p, 
+                status: "Downloading".to_string(),
+                downloaded,
+                total: total_size,
+                speed: current_speed,
+            });
+        }
+    }
+    drop(file);
+
+    // 2. Verify
+    sink.progress(DownloadProgress { 
+        progress: 100.0, 
+        status: "Verifying".to_string(),
+        downloaded: total_size,
+        total: total_size,
+        speed: 0.0,
+    });
+    
+    if !verify_hash(&target_path, checksum)? {
+        log::error!("Checksum mismatch for {} ({})", version, url);
+        log_err!(fs::remove_file(&target_path), "remove bad download");
+        return Err("Phiên bản tải về bị lỗi. Vui lòng tải lại.".into());
+    }
+
+    fs::rename(&target_path, &staged_path)?;
+    fs::write(staged_marker_path(version), normalize_checksum(checksum))?;
+    Ok(staged_path)
+}
+
+/// GET the archive, asking for the rest of `partial` if an earlier download was cut off.
+/// Returns the response and the offset it starts at (0 if the server sent the whole file).
+async fn request_archive(client: &Client, url: &str, partial: &Path) -> Result<(reqwest::Response, u64), Box<dyn std::error::Error>> {
+    let existing = fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    if existing > 0 {
+        let res = client.get(url).header(RANGE, format!("bytes={}-", existing)).send().await?;
+        let expected_range = format!("bytes {}-", existing);
+        let range_ok = res.headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with(&expected_range));
+        match res.status() {
+            StatusCode::PARTIAL_CONTENT if range_ok => return Ok((res, existing)),
+            // Server ignored the range and sent everything, start over with this response
+            StatusCode::OK => return Ok((res, 0)),
+            status => log::warn!("Can't resume download ({}), starting over", status),
+        }
+        log_err!(fs::remove_file(partial), "remove partial download");
+    }
+    let res = client.get(url).send().await?.error_for_status()?;
+    Ok((res, 0))
+}
+
+/// Extract a verified archive and swap it into game/
+pub fn install_archive(archive: &Path, version: &str, sink: &dyn ProgressSink) -> Result<(), Box<dyn std::error::Error>> {
+    let game_dir = paths::get_game_dir();
+
+    // 3. Extract (Atomic-ish)
+    sink.progress(DownloadProgress { 
+        progress: 100.0, 
+        status: "Installing".to_string(),
+        downloaded: 0,
+        total: 0,
+        speed: 0.0,
+    });
+    
+    // Next to game/ rather than in cache/, which can be on another drive in installed mode
+    let extract_path = paths::get_data_dir().join("game.extracting");
+    if extract_path.exists() {
+        fs::remove_dir_all(&extract_path)?;
+    }
+    fs::create_dir_all(&extract_path)?;
+
+    extract_archive(archive, &extract_path)?;
+
+    // Write version.txt BEFORE moving
+    fs::write(extract_path.join("version.txt"), version)?;
+
+    // 4. Move to game/
+    // The game may have been started while we were downloading, don't pull files out from under it
+    if let Some(running) = game::find_running_instance() {
+        return Err(Box::new(GameError::UpdateBlocked(running)));
+    }
+    if game_dir.exists() {
+        fs::remove_dir_all(&game_dir)?;
+    }
+    
+    // Rename/Move
+    // Note: rename only works on same filesystem, which is why we extract next to game/.
+    fs::rename(&extract_path, &game_dir)?;
+
+    // Cleanup
+    log_err!(fs::remove_file(archive), "remove installed archive");
+    log_err!(fs::remove_file(staged_marker_path(version)), "remove staged marker");
+
+    Ok(())
+}
+
+/// Manifest checksums may be "sha256:XXXX" or just "XXXX", in any case
+pub fn normalize_checksum(checksum: &str) -> String {
+    checksum.strip_prefix("sha256:").unwrap_or(checksum).to_lowercase()
+}
+
+/// Lowercase hex SHA-256 of a file
+pub fn sha256_file(path: &Path) -> Result<String, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+pub fn verify_hash(path: &Path, expected_hash_prefix: &str) -> Result<bool, std::io::Error> {
+    Ok(sha256_file(path)? == normalize_checksum(expected_hash_prefix))
+}
+
+/// What's wrong with one installed file compared to the release's file list
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileProblem {
+    Missing,
+    SizeMismatch,
+    HashMismatch,
+    Unreadable,
+}
+
+impl std::fmt::Display for FileProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FileProblem::Missing => "missing",
+            FileProblem::SizeMismatch => "size mismatch",
+            FileProblem::HashMismatch => "hash mismatch",
+            FileProblem::Unreadable => "unreadable",
+        })
+    }
+}
+
+/// Compare the files under `game_dir` with `list`. Sizes are checked first so only plausible files get hashed.
+pub fn check_files(game_dir: &Path, list: &FileList) -> Vec<(String, FileProblem)> {
+    list.files
+        .iter()
+        .filter_map(|file| {
+            let path = game_dir.join(&file.path);
+            let problem = match fs::metadata(&path) {
+                Err(_) => FileProblem::Missing,
+                Ok(meta) if meta.len() != file.size => FileProblem::SizeMismatch,
+                Ok(_) => match sha256_file(&path) {
+                    Ok(hash) if hash == file.sha256 => return None,
+                    Ok(_) => FileProblem::HashMismatch,
+                    Err(_) => FileProblem::Unreadable,
+                },
+            };
+            Some((file.path.clone(), problem))
+        })
+        .collect()
+}
+
+/// Release archives are zip or tar.zst (see `release::ArchiveFormat`), told apart by their magic bytes
+fn extract_archive(path: &Path, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut magic = [0u8; 4];
+    let is_zstd = File::open(path)?.read_exact(&mut magic).is_ok() && magic == ZSTD_MAGIC;
+    if is_zstd {
+        extract_tar_zst(path, target_dir)
+    } else {
+        extract_zip(path, target_dir)
+    }
+}
+
+fn extract_tar_zst(path: &Path, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let decoder = zstd::Decoder::new(BufReader::new(File::open(path)?))?;
+    // unpack() skips entries that would land outside target_dir
+    tar::Archive::new(decoder).unpack(target_dir)?;
+    Ok(())
+}
+
+fn extract_zip(zip_path: &Path, target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::open(zip_path)?;
+    let reader = BufReader::new(file);
+    let mut archive = ZipArchive::new(reader)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let outpath = match file.enclosed_name() {
+            Some(path) => target_dir.join(path),
+            None => continue,
+        };
+
+        if file.name().ends_with('/') {
+            fs::create_dir_all(&outpath)?;
+        } else {
+            if let Some(p) = outpath.parent() {
+                if !p.exists() {
+                    fs::create_dir_all(p)?;
+                }
+            }
+            let mut outfile = File::create(&outpath)?;
+            io::copy(&mut file, &mut outfile)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use crate::launcher_core::launch::{LaunchError, LaunchPlan};
+use crate::paths;
+
+#[derive(Debug, Clone)]
+pub struct RunningInstance {
+    pub pid: Option<u32>, // Unknown when we only detected a locked executable
+    pub exe: PathBuf,
+}
+
+impl fmt::Display for RunningInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pid {
+            Some(pid) => write!(f, "pid {}", pid),
+            None => write!(f, "{}", self.exe.display()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GameError {
+    #[error("The game is already running ({0})")]
+    AlreadyRunning(RunningInstance),
+    #[error("Close the game before updating ({0})")]
+    UpdateBlocked(RunningInstance),
+    #[error("An update is being installed, please wait")]
+    Installing,
+    #[error("Game executable not found at {0:?}")]
+    ExecutableMissing(PathBuf),
+    #[error(transparent)]
+    Launch(#[from] LaunchError),
+    #[error("Failed to start the game: {0}")]
+    Spawn(#[from] std::io::Error),
+}
+
+pub fn get_local_version() -> String {
+    let path = paths::get_game_dir().join("version.txt");
+    if path.exists() {
+        if let Ok(content) = fs::read_to_string(path) {
+            return content.trim().to_string();
+        }
+    }
+    "0.0.0".to_string()
+}
+
+/// Any process whose executable lives under game/, including ones we didn't launch
+pub fn find_running_instance() -> Option<RunningInstance> {
+    let game_dir = paths::get_game_dir();
+    let game_dir = fs::canonicalize(&game_dir).unwrap_or(game_dir);
+    scan_processes_in(&game_dir)
+}
+
+#[cfg(target_os = "linux")]
+fn scan_processes_in(dir: &Path) -> Option<RunningInstance> {
+    let entries = fs::read_dir("/proc").ok()?;
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Only readable for our own processes, which is all we care about
+        if let Ok(exe) = fs::read_link(entry.path().join("exe")) {
+            if exe.starts_with(dir) {
+                return Some(RunningInstance { pid: Some(pid), exe });
+            }
+        }
+    }
+    None
+}
+
+/// No portable process list without extra deps, so look for locked executables instead
+#[cfg(not(target_os = "linux"))]
+fn scan_processes_in(dir: &Path) -> Option<RunningInstance> {
+    let entries = fs::read_dir(dir).ok()?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(found) = scan_processes_in(&path) {
+                return Some(found);
+            }
+        } else if path.extension().map(|e| e.eq_ignore_ascii_case("exe")).unwrap_or(false) && is_locked(&path) {
+            return Some(RunningInstance { pid: None, exe: path });
+        }
+    }
+    None
+}
+
+/// Opening a running executable for writing fails with a sharing violation / ETXTBSY
+#[cfg(not(target_os = "linux"))]
+fn is_locked(path: &Path) -> bool {
+    #[cfg(windows)]
+    const EXE_BUSY_CODE: i32 = 32; // ERROR_SHARING_VIOLATION
+    #[cfg(not(windows))]
+    const EXE_BUSY_CODE: i32 = 26; // ETXTBSY
+
+    match fs::OpenOptions::new().append(true).open(path) {
+        Ok(_) => false,
+        Err(e) => e.raw_os_error() == Some(EXE_BUSY_CODE),
+    }
+}
+
+/// With `log`, the game's stdout and stderr both go straight into that file
+pub fn launch_game(exe_name: &str, plan: &LaunchPlan, log: Option<&File>) -> Result<Child, GameError> {
+    let exe_path = paths::get_game_dir().join(exe_name);
+    
+    if !exe_path.exists() {
+        return Err(GameError::ExecutableMissing(exe_path));
+    }
+    if let Some(running) = find_running_instance() {
+        return Err(GameError::AlreadyRunning(running));
+    }
+
+    let mut cmd = Command::new(&exe_path);
+    cmd.args(&plan.args)
+        .envs(&plan.env)
+        .current_dir(&plan.working_dir);
+    if let Some(log) = log {
+        cmd.stdout(Stdio::from(log.try_clone()?)).stderr(Stdio::from(log.try_clone()?));
+    }
+
+    // Caller hands the child to the supervisor
+    Ok(cmd.spawn()?)
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use crate::config::LaunchSettings;
+use crate::launcher_core::manifest::ManifestLaunch;
+
+/// Named set of launch options, e.g. "Windowed", "Safe mode", "Dev console"
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LaunchProfile {
+    pub name: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub working_dir: Option<String>, // Relative to game/
+}
+
+/// Final command line for one launch
+#[derive(Clone, Debug, PartialEq)]
+pub struct LaunchPlan {
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub working_dir: PathBuf,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LaunchError {
+    #[error("Unknown launch profile '{0}'")]
+    UnknownProfile(String),
+    #[error("Argument '{0}' is locked by the game and can't be changed")]
+    LockedArg(String),
+    #[error("Working directory '{0}' must stay inside the game folder")]
+    InvalidWorkingDir(String),
+    #[error("Unknown server '{0}'")]
+    UnknownServer(String),
+}
+
+/// "-server=prod" and "-server" both configure "-server"
+fn arg_key(arg: &str) -> &str {
+    arg.split('=').next().unwrap_or(arg)
+}
+
+/// User args may not name a flag the manifest locked, in either `-key=value` or `-key value` form.
+/// Only locked flags count, so the "prod" in a locked `-server prod` doesn't block a user value "prod".
+/// Engines tend to read flags case-insensitively, so `-Server` is the same flag.
+fn check_not_locked(args: &[String], locked: &[String]) -> Result<(), LaunchError> {
+    let locked_keys: Vec<&str> = locked.iter().filter(|l| l.starts_with('-')).map(|l| arg_key(l)).collect();
+    for arg in args {
+        if locked_keys.iter().any(|key| key.eq_ignore_ascii_case(arg_key(arg))) {
+            return Err(LaunchError::LockedArg(arg.clone()));
+        }
+    }
+    Ok(())
+}
+
+fn resolve_working_dir(game_dir: &Path, relative: &str) -> Result<PathBuf, LaunchError> {
+    let rel = Path::new(relative);
+    // Check '\'-separated parts too, a config written on Windows can carry "..\" paths
+    let escapes = rel.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        || relative.split(['/', '\\']).any(|part| part == "..");
+    if escapes {
+        return Err(LaunchError::InvalidWorkingDir(relative.to_string()));
+    }
+    Ok(game_dir.join(rel))
+}
+
+/// All profiles on offer. A user profile replaces a manifest profile with the same name.
+pub fn available_profiles(manifest: Option<&ManifestLaunch>, settings: &LaunchSettings) -> Vec<LaunchProfile> {
+    let mut profiles: Vec<LaunchProfile> = manifest.map(|m| m.profiles.clone()).unwrap_or_default();
+    for p in &settings.profiles {
+        profiles.retain(|existing| existing.name != p.name);
+        profiles.push(p.clone());
+    }
+    profiles
+}
+
+/// Combine manifest and user options into a launch plan.
+/// Order: locked args, manifest defaults, user extra args, profile args, server args. Env: user, then profile.
+pub fn build_plan(
+    manifest: Option<&ManifestLaunch>,
+    settings: &LaunchSettings,
+    profile: Option<&str>,
+    server: Option<&str>,
+    game_dir: &Path,
+) -> Result<LaunchPlan, LaunchError> {
+    let locked_args: &[String] = manifest.map(|m| m.locked_args.as_slice()).unwrap_or(&[]);
+    let default_args: &[String] = manifest.map(|m| m.default_args.as_slice()).unwrap_or(&[]);
+
+    let profiles = available_profiles(manifest, settings);
+    let profile = match profile.or(settings.default_profile.as_deref()) {
+        Some(name) => Some(
+            profiles
+                .iter()
+                .find(|p| p.name == name)
+                .ok_or_else(|| LaunchError::UnknownProfile(name.to_string()))?,
+        ),
+        None => None,
+    };
+
+    check_not_locked(&settings.extra_args, locked_args)?;
+    if let Some(p) = profile {
+        check_not_locked(&p.args, locked_args)?;
+    }
+
+    let mut args: Vec<String> = locked_args.to_vec();
+    args.extend(default_args.iter().cloned());
+    args.extend(settings.extra_args.iter().cloned());
+
+    let mut env = settings.env.clone();
+    let mut working_dir = game_dir.to_path_buf();
+    if let Some(p) = profile {
+        args.extend(p.args.iter().cloned());
+        env.extend(p.env.clone());
+        if let Some(dir) = &p.working_dir {
+            working_dir = resolve_working_dir(game_dir, dir)?;
+        }
+    }
+
+    if let Some(id) = server {
+        let server = manifest
+            .and_then(|m| m.servers.iter().find(|s| s.id == id))
+            .ok_or_else(|| LaunchError::UnknownServer(id.to_string()))?;
+        args.extend(server.args.iter().cloned());
+    }
+
+    Ok(LaunchPlan { args, env, working_dir })
+}
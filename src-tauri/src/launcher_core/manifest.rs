@@ -0,0 +1,391 @@
+use reqwest::header::{HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::collections::HashMap;
+use crate::launcher_core::compat;
+use crate::launcher_core::launch::LaunchProfile;
+use crate::launcher_core::signing;
+use crate::paths;
+use crate::logger::log_err;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NewsItem {
+    #[serde(default)]
+    pub id: Option<String>, // For ganhrong://news/<id> links
+    pub title: String,
+    pub image: String,
+    pub date: String,
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+/// Launch options set by us. `locked_args` are always passed and players can't override them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ManifestLaunch {
+    #[serde(default)]
+    pub default_args: Vec<String>,
+    #[serde(default)]
+    pub locked_args: Vec<String>,
+    #[serde(default)]
+    pub profiles: Vec<LaunchProfile>,
+    #[serde(default)]
+    pub servers: Vec<JoinServer>,
+}
+
+/// Server players can join from a ganhrong://join?server=<id> link. Only listed servers can be joined
+/// and the args come from here, never from the link.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JoinServer {
+    pub id: String,
+    #[serde(default)]
+    pub args: Vec<String>, // e.g. ["-connect=asia1.ganhrong.tech:7777"]
+}
+
+/// One file of a release build, as listed in `<release>.files.json`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FileHash {
+    pub path: String, // Relative, '/' separated
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Per-file hashes of a release, written next to the archive by the release tool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FileList {
+    pub version: String,
+    pub files: Vec<FileHash>,
+}
+
+/// Upcoming version players can download ahead of release
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PreloadInfo {
+    pub version: String,
+    pub archive: String,
+    pub checksum: String,
+    pub unlock_at: i64, // Unix timestamp (seconds)
+}
+
+/// Signed launcher build for self-update. `signature` is base64 Ed25519 over the binary.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LauncherUpdate {
+    pub version: String,
+    pub url: String,
+    pub checksum: String,
+    pub signature: String,
+}
+
+/// Schema this launcher writes and understands. Older manifests are migrated up on load.
+/// 1: no `schema_version`, single `background` string. 2: `backgrounds` list.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// Deserialize a non-critical field, falling back to its default if it's malformed
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_else(|e| {
+        log::warn!("Ignoring malformed manifest section: {}", e);
+        T::default()
+    }))
+}
+
+/// Like `lenient`, but keeps the well-formed entries of a list
+fn lenient_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let items: Vec<serde_json::Value> = lenient(deserializer)?;
+    Ok(items.into_iter()
+        .filter_map(|item| serde_json::from_value(item)
+            .map_err(|e| log::warn!("Ignoring malformed manifest entry: {}", e))
+            .ok())
+        .collect())
+}
+
+/// Cosmetic parts of the manifest. None of it is needed to install or play, so it never fails parsing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ManifestPresentation {
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub backgrounds: Vec<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub sidebar_links: Option<HashMap<String, String>>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub news: Vec<NewsItem>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub languages: Vec<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub maintenance_message: Option<String>,
+}
+
+/// The critical core (versions, archive, checksum) is strict, everything else is optional
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameManifest {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    pub game_name: String,
+    #[serde(default)]
+    pub game_exe: Option<String>,  // Name of the game executable
+    pub latest_version: String,
+    pub game_zip: String,
+    pub checksum: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub file_list: Option<String>,  // URL of the release's FileList, used to verify installs
+    #[serde(default)]
+    pub server_status: Option<String>,  // "online", "maintenance", "closed"
+    #[serde(default)]
+    pub min_launcher_version: Option<String>,
+    #[serde(flatten)]
+    pub presentation: ManifestPresentation,
+    #[serde(default, deserialize_with = "lenient")]
+    pub preload: Option<PreloadInfo>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub launch: Option<ManifestLaunch>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub crash_report_url: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub launcher_update: Option<LauncherUpdate>,
+}
+
+/// v1 had a single background image
+fn migrate_v1(obj: &mut serde_json::Map<String, serde_json::Value>) {
+    if let Some(bg) = obj.remove("background") {
+        if !obj.contains_key("backgrounds") {
+            obj.insert("backgrounds".to_string(), serde_json::Value::Array(vec![bg]));
+        }
+    }
+}
+
+/// `MIGRATIONS[n]` upgrades schema n+1 to n+2
+const MIGRATIONS: &[fn(&mut serde_json::Map<String, serde_json::Value>)] = &[migrate_v1];
+
+/// Upgrade a manifest from an older schema, one version at a time.
+/// Newer schemas are left alone and parsed as far as we understand them.
+fn migrate(mut raw: serde_json::Value) -> serde_json::Value {
+    let Some(obj) = raw.as_object_mut() else {
+        return raw;
+    };
+    let mut version = obj.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+    while version < CURRENT_SCHEMA_VERSION {
+        if let Some(step) = MIGRATIONS.get(version.max(1) as usize - 1) {
+            step(obj);
+        }
+        version += 1;
+        obj.insert("schema_version".to_string(), version.into());
+    }
+    raw
+}
+
+/// The manifest didn't parse and says it needs a newer launcher than us
+#[derive(Debug, thiserror::Error)]
+#[error("This launcher is too old to read the manifest, version {required} or newer is required")]
+pub struct UnsupportedManifest {
+    pub required: String,
+    pub update: Option<LauncherUpdate>,
+}
+
+/// `manifest.json.sig` didn't match the manifest body
+#[derive(Debug, thiserror::Error)]
+#[error("Manifest signature is invalid")]
+pub struct InvalidSignature;
+
+/// Fetch `<url>.sig` (base64 Ed25519 over the exact body, written by the release tool) and check it
+pub async fn check_signature_with(url: &str, body: &[u8], public_key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let signature = reqwest::get(format!("{}.sig", url)).await?.error_for_status()?.text().await?;
+    if !signing::verify_with(public_key, body, &signature)? {
+        return Err(Box::new(InvalidSignature));
+    }
+    Ok(())
+}
+
+/// Launchers built with a public key only accept signed manifests
+async fn check_signature(url: &str, body: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    match signing::public_key() {
+        Some(key) => check_signature_with(url, body, key).await,
+        None => Ok(()),
+    }
+}
+
+/// Parse a manifest body. If it doesn't fit our schema, check whether it was
+/// written for a newer launcher so we can self-update instead of showing a serde error.
+pub fn parse_manifest(body: &[u8]) -> Result<GameManifest, Box<dyn std::error::Error>> {
+    let raw = migrate(serde_json::from_slice(body)?);
+    let err = match GameManifest::deserialize(&raw) {
+        Ok(manifest) => return Ok(manifest),
+        Err(e) => e,
+    };
+    if let Some(required) = raw.get("min_launcher_version").and_then(|v| v.as_str()) {
+        if compat::is_too_old(required) {
+            let update = raw.get("launcher_update").and_then(|u| serde_json::from_value(u.clone()).ok());
+            return Err(Box::new(UnsupportedManifest { required: required.to_string(), update }));
+        }
+    }
+    Err(Box::new(err))
+}
+
+/// HTTP validators from the last successful fetch, stored next to the cached manifest
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    fn from_response(resp: &reqwest::Response) -> Self {
+        let header = |name: HeaderName| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+}
+
+/// What changed between two manifests, sent with `manifest-updated`
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ManifestDiff {
+    pub new_version: Option<String>,
+    pub new_news: Vec<String>,           // Titles of news items we haven't seen
+    pub server_status: Option<String>,   // Set when the status changed
+}
+
+impl ManifestDiff {
+    pub fn between(old: Option<&GameManifest>, new: &GameManifest) -> Self {
+        let Some(old) = old else {
+            return Self {
+                new_version: Some(new.latest_version.clone()),
+                new_news: new.presentation.news.iter().map(|n| n.title.clone()).collect(),
+                server_status: new.server_status.clone(),
+            };
+        };
+
+        Self {
+            new_version: (old.latest_version != new.latest_version)
+                .then(|| new.latest_version.clone()),
+            new_news: new.presentation.news.iter()
+                .filter(|n| !old.presentation.news.iter().any(|o| o.title == n.title && o.date == n.date))
+                .map(|n| n.title.clone())
+                .collect(),
+            server_status: (old.server_status != new.server_status)
+                .then(|| new.server_status.clone().unwrap_or_else(|| "online".to_string())),
+        }
+    }
+}
+
+pub enum ManifestFetch {
+    Unchanged,
+    Changed { manifest: Box<GameManifest>, diff: ManifestDiff },
+}
+
+async fn request_manifest(url: &str, validators: Option<&CacheValidators>) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    let mut req = client.get(url);
+    if let Some(v) = validators {
+        if let Some(etag) = &v.etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = &v.last_modified {
+            req = req.header(IF_MODIFIED_SINCE, modified);
+        }
+    }
+
+    // 304 isn't an error status, anything 4xx/5xx is and never reaches the parser or the cache
+    Ok(req.send().await?.error_for_status()?)
+}
+
+/// Per-file hashes published at a manifest's `file_list` URL
+pub async fn fetch_file_list(url: &str) -> Result<FileList, reqwest::Error> {
+    reqwest::get(url).await?.error_for_status()?.json().await
+}
+
+pub async fn fetch_manifest(url: &str) -> Result<GameManifest, Box<dyn std::error::Error>> {
+    let resp = request_manifest(url, None).await?;
+    let validators = CacheValidators::from_response(&resp);
+    let body = resp.bytes().await?;
+    check_signature(url, &body).await?;
+    let manifest = parse_manifest(&body)?;
+    
+    // Save to cache
+    save_manifest_cache(&manifest, &validators);
+    
+    Ok(manifest)
+}
+
+/// Conditional fetch against the cached copy. Only reports `Changed` when the content differs.
+pub async fn fetch_manifest_if_changed(url: &str) -> Result<ManifestFetch, Box<dyn std::error::Error>> {
+    let previous = load_cached_manifest();
+    // Without a cached body a 304 would leave us with nothing, so only send validators if we have one
+    let validators = previous.as_ref().map(|_| load_validators());
+
+    let resp = request_manifest(url, validators.as_ref()).await?;
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        return Ok(ManifestFetch::Unchanged);
+    }
+
+    let validators = CacheValidators::from_response(&resp);
+    let body = resp.bytes().await?;
+    check_signature(url, &body).await?;
+    let manifest = parse_manifest(&body)?;
+    save_manifest_cache(&manifest, &validators);
+
+    if previous.as_ref() == Some(&manifest) {
+        return Ok(ManifestFetch::Unchanged);
+    }
+    let diff = ManifestDiff::between(previous.as_ref(), &manifest);
+    Ok(ManifestFetch::Changed { manifest: Box::new(manifest), diff })
+}
+
+pub fn load_cached_manifest() -> Option<GameManifest> {
+    let path = paths::get_cache_dir().join("manifest.json");
+    let content = fs::read(&path).ok()?;
+    let manifest = match parse_manifest(&content) {
+        Ok(m) => m,
+        Err(e) => {
+            log::warn!("Cached manifest is unreadable: {}", e);
+            return None;
+        }
+    };
+
+    // Written by an older launcher, store it in the current schema so we only migrate once
+    let cached_version = serde_json::from_slice::<serde_json::Value>(&content).ok()
+        .and_then(|v| v.get("schema_version").and_then(|v| v.as_u64()));
+    if cached_version != Some(manifest.schema_version as u64) {
+        log::info!("Migrated cached manifest to schema {}", manifest.schema_version);
+        if let Ok(json) = serde_json::to_string_pretty(&manifest) {
+            log_err!(fs::write(&path, json), "rewrite cached manifest");
+        }
+    }
+    Some(manifest)
+}
+
+fn load_validators() -> CacheValidators {
+    let path = paths::get_cache_dir().join("manifest.meta.json");
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest_cache(manifest: &GameManifest, validators: &CacheValidators) {
+    if let Err(e) = write_manifest_cache(manifest, validators) {
+        log::warn!("Failed to cache manifest: {}", e);
+    }
+}
+
+fn write_manifest_cache(manifest: &GameManifest, validators: &CacheValidators) -> std::io::Result<()> {
+    let cache_dir = paths::get_cache_dir();
+    fs::create_dir_all(&cache_dir)?;
+    fs::write(cache_dir.join("manifest.json"), serde_json::to_string_pretty(manifest)?)?;
+    fs::write(cache_dir.join("manifest.meta.json"), serde_json::to_string_pretty(validators)?)
+}
@@ -0,0 +1,11 @@
+//! Manifest, download/install and game logic shared by the UI, `--headless` and the release tool.
+//! Nothing in here may import `tauri`: progress goes through `ProgressSink` and state is passed in.
+
+pub mod compat;
+pub mod download;
+pub mod game;
+pub mod launch;
+pub mod manifest;
+pub mod net;
+pub mod progress;
+pub mod signing;
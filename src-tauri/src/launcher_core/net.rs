@@ -0,0 +1,70 @@
+use std::time::Duration;
+use serde::Serialize;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Plain HTTP endpoint that always answers 204 with an empty body. Portals can't leave plain HTTP
+/// alone, they answer it with a redirect or their login page instead.
+pub const CAPTIVE_CHECK_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+#[derive(Clone, Copy, Serialize, Debug, PartialEq)]
+pub enum Connectivity {
+    Unknown,
+    Online,
+    Offline,
+    CaptivePortal, // Network is up but requests are intercepted by a login page
+}
+
+impl Connectivity {
+    pub fn is_online(self) -> bool {
+        self == Connectivity::Online
+    }
+}
+
+/// Work out what a failed request says about our connection.
+/// Anything that isn't a connect/timeout failure means the host did answer.
+pub fn classify_error(err: &(dyn std::error::Error + 'static)) -> Connectivity {
+    if let Some(e) = err.downcast_ref::<reqwest::Error>() {
+        if e.is_connect() || e.is_timeout() {
+            return Connectivity::Offline;
+        }
+    }
+    Connectivity::Online
+}
+
+/// True if the check endpoint answered with anything but a success and an empty body.
+/// No answer at all tells us nothing, that's for the host probe to classify.
+async fn is_intercepted(client: &reqwest::Client, check_url: &str) -> bool {
+    let Ok(resp) = client.get(check_url).send().await else {
+        return false;
+    };
+    if !resp.status().is_success() {
+        return true;
+    }
+    !resp.bytes().await.is_ok_and(|body| body.is_empty())
+}
+
+/// Lightweight HEAD against the manifest host, and a captive portal check only if that fails
+pub async fn probe(url: &str) -> Connectivity {
+    probe_with(url, CAPTIVE_CHECK_URL).await
+}
+
+/// `probe` with another captive check endpoint, which must answer with an empty body
+pub async fn probe_with(url: &str, check_url: &str) -> Connectivity {
+    let client = match reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(c) => c,
+        Err(_) => return Connectivity::Unknown,
+    };
+    let err = match client.head(url).send().await {
+        Ok(_) => return Connectivity::Online,
+        Err(e) => e,
+    };
+    // A portal usually shows up as a TLS or connect failure against the real host
+    if is_intercepted(&client, check_url).await {
+        return Connectivity::CaptivePortal;
+    }
+    classify_error(&err)
+}
@@ -0,0 +1,71 @@
+use serde::Serialize;
+use std::sync::Mutex;
+
+// Progress report, sent to the frontend as the payload of the progress events
+#[derive(Clone, Serialize, Debug, PartialEq)]
+pub struct DownloadProgress {
+    pub progress: f32,        // 0.0 to 100.0
+    pub status: String,       // "Downloading", "Verifying", "Installing"
+    pub downloaded: u64,      // bytes downloaded
+    pub total: u64,           // total bytes
+    pub speed: f64,           // bytes per second
+}
+
+/// Where core operations report progress. The UI forwards it as a Tauri event,
+/// the CLI prints it and tests record it.
+pub trait ProgressSink: Send + Sync {
+    fn progress(&self, progress: DownloadProgress);
+}
+
+/// Discards everything
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn progress(&self, _progress: DownloadProgress) {}
+}
+
+/// Prints a line whenever the whole percentage or the phase changes
+#[derive(Default)]
+pub struct CliProgress {
+    last: Mutex<Option<(String, i32)>>,
+}
+
+impl ProgressSink for CliProgress {
+    fn progress(&self, p: DownloadProgress) {
+        let mut last = self.last.lock().unwrap();
+        let current = (p.status.clone(), p.progress as i32);
+        if last.as_ref() != Some(&current) {
+            println!("{} {}% ({}/{} bytes)", p.status, current.1, p.downloaded, p.total);
+            *last = Some(current);
+        }
+    }
+}
+
+/// Keeps every report so tests can assert on them
+#[derive(Default)]
+pub struct RecordingProgress {
+    events: Mutex<Vec<DownloadProgress>>,
+}
+
+impl RecordingProgress {
+    pub fn events(&self) -> Vec<DownloadProgress> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Phases in the order they were first reported, e.g. ["Downloading", "Verifying", "Installing"]
+    pub fn phases(&self) -> Vec<String> {
+        let mut phases: Vec<String> = Vec::new();
+        for e in self.events.lock().unwrap().iter() {
+            if phases.last() != Some(&e.status) {
+                phases.push(e.status.clone());
+            }
+        }
+        phases
+    }
+}
+
+impl ProgressSink for RecordingProgress {
+    fn progress(&self, progress: DownloadProgress) {
+        self.events.lock().unwrap().push(progress);
+    }
+}
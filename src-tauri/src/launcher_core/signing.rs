@@ -0,0 +1,27 @@
+//! Ed25519 signatures on launcher updates and manifests. The release tool signs with a 32 byte seed,
+//! the launcher checks against the matching public key built into it.
+
+use base64::Engine;
+use ed25519_dalek::{Signature, VerifyingKey};
+
+/// Key launcher builds are signed with (hex), set with `LAUNCHER_PUBLIC_KEY=... npm run tauri build`.
+/// Builds without one have no way to check an update or a manifest signature.
+pub fn public_key() -> Option<&'static str> {
+    option_env!("LAUNCHER_PUBLIC_KEY").map(str::trim).filter(|k| !k.is_empty())
+}
+
+/// Check a base64 signature over `data` against a hex public key
+pub fn verify_with(public_key: &str, data: &[u8], signature: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let key_bytes: [u8; 32] = hex::decode(public_key.trim())?
+        .try_into()
+        .map_err(|_| "Invalid launcher public key")?;
+    let key = VerifyingKey::from_bytes(&key_bytes)?;
+    let sig_bytes = base64::engine::general_purpose::STANDARD.decode(signature.trim())?;
+    let signature = Signature::from_slice(&sig_bytes)?;
+    Ok(key.verify_strict(data, &signature).is_ok())
+}
+
+/// `verify_with` the key built into this launcher
+pub fn verify(data: &[u8], signature: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    verify_with(public_key().ok_or("No launcher public key")?, data, signature)
+}
@@ -1,98 +1,138 @@
-mod config;
+mod auto_update;
+pub mod autostart;
+pub mod cli;
+pub mod config;
+mod connectivity;
+pub mod crash;
+pub mod deep_link;
+mod diagnostics;
 mod downloader;
-mod game;
-mod manifest;
-mod paths;
+mod game_logs;
+mod headless;
+pub mod launcher_core;
+mod logger;
+pub mod paths;
+pub mod playtime;
+mod poller;
+pub mod preload;
+pub mod release;
+mod self_update;
+pub mod single_instance;
 mod state;
+pub mod supervisor;
 mod tray;
+pub mod validate;
 
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 use crate::state::{AppState, GameStatus};
 use crate::config::LauncherConfig;
-use crate::manifest::GameManifest;
+use crate::connectivity::Connectivity;
+use crate::launcher_core::{compat, game, launch, manifest};
+use crate::launcher_core::game::GameError;
+use crate::launcher_core::launch::LaunchProfile;
+use crate::launcher_core::manifest::{GameManifest, ManifestLaunch, UnsupportedManifest};
+use crate::logger::log_err;
 
 const MANIFEST_URL: &str = "https://pub-22ad0c737da74e26888b5a5c396658c5.r2.dev/manifest.json"; // TODO: Replace with actual R2 URL
+/// How long a link opened at startup waits for the first manifest fetch
+const DEEP_LINK_MANIFEST_WAIT_SECS: u64 = 30;
 
 #[tauri::command]
 async fn get_manifest(app: AppHandle, force_refresh: bool) -> Result<GameManifest, String> {
     let state = app.state::<AppState>();
-    
-    // Check internet
-    let client = reqwest::Client::new();
-    let is_online = client.get("https://1.1.1.1").send().await.is_ok();
-
-    if !is_online {
-        *state.status.lock().unwrap() = GameStatus::Offline;
-        // Try load cache
-        if let Some(cached) = manifest::load_cached_manifest() {
-            *state.manifest.lock().unwrap() = Some(cached.clone());
-            return Ok(cached);
-        } else {
-            return Err("Offline and no cached manifest".to_string());
-        }
-    }
 
-    if force_refresh {
-         match manifest::fetch_manifest(MANIFEST_URL).await {
-            Ok(m) => {
-                *state.manifest.lock().unwrap() = Some(m.clone());
-                *state.status.lock().unwrap() = GameStatus::Checking;
-                Ok(m)
-            },
-            Err(e) => Err(e.to_string()),
-        }
-    } else {
-        // Return cached if exists, else fetch
+    if !force_refresh {
+        // Return cached if exists
         let existing = { state.manifest.lock().unwrap().clone() };
         if let Some(m) = existing {
-            Ok(m)
-        } else {
-             match manifest::fetch_manifest(MANIFEST_URL).await {
-                Ok(m) => {
-                    *state.manifest.lock().unwrap() = Some(m.clone());
-                    Ok(m)
-                },
-                Err(e) => Err(e.to_string()),
+            return Ok(m);
+        }
+
+        // We found out we're offline moments ago, don't wait on the network again
+        let known = { state.connectivity.lock().unwrap().fresh() };
+        if matches!(known, Some(Connectivity::Offline) | Some(Connectivity::CaptivePortal)) {
+            return load_offline_manifest(&app);
+        }
+    }
+
+    // The manifest fetch itself tells us whether we're online
+    let (classified, message) = match manifest::fetch_manifest(MANIFEST_URL).await {
+        Ok(m) => {
+            connectivity::report(&app, Connectivity::Online);
+            *state.manifest.lock().unwrap() = Some(m.clone());
+            poller::refresh_status(&app);
+            self_update::maybe_run(&app);
+            return Ok(m);
+        },
+        Err(e) => {
+            if let Some(unsupported) = e.downcast_ref::<UnsupportedManifest>() {
+                connectivity::report(&app, Connectivity::Online);
+                *state.status.lock().unwrap() = GameStatus::LauncherOutdated;
+                log_err!(app.emit("status-changed", GameStatus::LauncherOutdated), "emit status-changed");
+                self_update::spawn(app.clone(), unsupported.update.clone());
+                return Err(e.to_string());
             }
+            (connectivity::classify_error(e.as_ref()), e.to_string())
         }
+    };
+    let observed = connectivity::report_failure(&app, MANIFEST_URL, classified).await;
+    if observed.is_online() {
+        // Host answered, the manifest itself is the problem
+        Err(message)
+    } else {
+        load_offline_manifest(&app)
     }
 }
 
+fn load_offline_manifest(app: &AppHandle) -> Result<GameManifest, String> {
+    let state = app.state::<AppState>();
+    if let Some(cached) = manifest::load_cached_manifest() {
+        *state.manifest.lock().unwrap() = Some(cached.clone());
+        poller::refresh_status(app);
+        Ok(cached)
+    } else {
+        Err("Offline and no cached manifest".to_string())
+    }
+}
+
+#[tauri::command]
+async fn get_connectivity(app: AppHandle, force: bool) -> Connectivity {
+    connectivity::check(&app, MANIFEST_URL, force).await
+}
+
+#[tauri::command]
+fn get_status(app: AppHandle) -> GameStatus {
+    app.state::<AppState>().status.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn check_for_updates(app: AppHandle) {
+    app.state::<AppState>().poller.trigger();
+}
+
+#[tauri::command]
+fn set_update_polling(app: AppHandle, paused: bool) {
+    app.state::<AppState>().poller.set_paused(paused);
+}
+
+/// Download the manifest's upcoming version now, it installs itself once released
+#[tauri::command]
+fn start_preload(app: AppHandle) -> Result<(), String> {
+    preload::start(&app)
+}
+
 #[tauri::command]
 async fn start_download(app: AppHandle) -> Result<(), String> {
     let state = app.state::<AppState>();
     let manifest = { state.manifest.lock().unwrap().clone() };
     
+    if let Some(running) = supervisor::running_instance(&app) {
+        return Err(GameError::UpdateBlocked(running).to_string());
+    }
     if let Some(m) = manifest {
-        *state.status.lock().unwrap() = GameStatus::Downloading(0.0);
-        
-        let app_handle = app.clone();
-        let app_handle_2 = app.clone();
-        
-        // Spawn download task
-        tauri::async_runtime::spawn(async move {
-            let res = downloader::download_and_install_game(
-                &m.game_zip, 
-                &m.checksum, 
-                &m.latest_version,
-                app_handle, 
-                move |_, _| {} // helper callback if needed, but we emit events
-            ).await;
-            
-            let state = app_handle_2.state::<AppState>();
-            match res {
-                Ok(_) => {
-                    *state.status.lock().unwrap() = GameStatus::ReadyToPlay;
-                    let _ = app_handle_2.emit("download-complete", ());
-                },
-                Err(e) => {
-                    *state.status.lock().unwrap() = GameStatus::UpdateAvailable; // Reset or Error state
-                    let _ = app_handle_2.emit("download-error", e.to_string());
-                }
-            }
-        });
-        
-        Ok(())
+        compat::check(&m).map_err(|e| e.to_string())?;
+        downloader::spawn_install(app.clone(), m)
     } else {
         Err("No manifest loaded".to_string())
     }
@@ -104,18 +144,26 @@ fn get_local_version() -> String {
 }
 
 #[tauri::command]
-fn launch_game(app: AppHandle) -> Result<(), String> {
+fn launch_game(app: AppHandle, profile: Option<String>) -> Result<(), String> {
+    start_game(&app, profile.as_deref(), None)
+}
+
+/// Launch with an optional profile and server to join (`launch_game` and deep links)
+fn start_game(app: &AppHandle, profile: Option<&str>, server: Option<&str>) -> Result<(), String> {
     let state = app.state::<AppState>();
     let manifest = state.manifest.lock().unwrap().clone();
     
     // Strict check
     let local = game::get_local_version();
     let exe_name: String;
+    let manifest_launch: Option<ManifestLaunch>;
     if let Some(m) = manifest {
+        compat::check(&m).map_err(|e| e.to_string())?;
         if local != m.latest_version {
             return Err("Version mismatch. Please update.".to_string());
         }
         exe_name = m.game_exe.clone().unwrap_or_else(|| "game.exe".to_string());
+        manifest_launch = m.launch.clone();
     } else {
         // If offline and can play? User said "Manifest Fetch ... when launcher start".
         // If offline, we might have cached manifest.
@@ -130,15 +178,33 @@ fn launch_game(app: AppHandle) -> Result<(), String> {
         return Err("Cannot verify version (Offline or No Manifest)".to_string());
     }
 
-    game::launch_game(&exe_name).map_err(|e| e.to_string())?;
+    if matches!(*state.status.lock().unwrap(), GameStatus::Downloading(_) | GameStatus::Updating) {
+        return Err(GameError::Installing.to_string());
+    }
+    if let Some(running) = supervisor::running_instance(app) {
+        return Err(GameError::AlreadyRunning(running).to_string());
+    }
+    let settings = { state.config.lock().unwrap().launch.clone() };
+    let plan = launch::build_plan(manifest_launch.as_ref(), &settings, profile, server, &paths::get_game_dir())
+        .map_err(|e| e.to_string())?;
+    let log = game_logs::create_session_log()
+        .map_err(|e| log::warn!("Failed to create game log: {}", e))
+        .ok();
+    let child = game::launch_game(&exe_name, &plan, log.as_ref().map(|(file, _)| file)).map_err(|e| e.to_string())?;
+    supervisor::supervise(app, child, local, log.map(|(_, path)| path));
     
     // Auto-close if configured
     let config = state.config.lock().unwrap();
     match config.close_behavior {
-        crate::config::CloseBehavior::Exit => app.exit(0),
+        crate::config::CloseBehavior::Exit => {
+            if let Some(running) = supervisor::current(app) {
+                log_err!(playtime::record_unfinished(&running.version, &running.started_at), "record play session");
+            }
+            app.exit(0)
+        }
         crate::config::CloseBehavior::MinimizeToTray => {
             if let Some(window) = app.get_webview_window("main") {
-                let _ = window.hide();
+                log_err!(window.hide(), "window hide");
             }
         },
     }
@@ -146,6 +212,67 @@ fn launch_game(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+fn get_launch_profiles(app: AppHandle) -> Vec<LaunchProfile> {
+    let state = app.state::<AppState>();
+    let manifest_launch = { state.manifest.lock().unwrap().as_ref().and_then(|m| m.launch.clone()) };
+    let settings = { state.config.lock().unwrap().launch.clone() };
+    launch::available_profiles(manifest_launch.as_ref(), &settings)
+}
+
+#[tauri::command]
+fn get_running_game(app: AppHandle) -> Option<supervisor::RunningGame> {
+    supervisor::current(&app)
+}
+
+#[tauri::command]
+fn get_play_stats() -> playtime::PlayStats {
+    playtime::get_play_stats()
+}
+
+#[tauri::command]
+fn list_game_logs() -> Vec<game_logs::GameLogInfo> {
+    game_logs::list_game_logs()
+}
+
+#[tauri::command]
+fn read_game_log(name: String, max_bytes: Option<u64>) -> Result<String, String> {
+    game_logs::read_game_log(&name, max_bytes)
+}
+
+#[tauri::command]
+async fn upload_crash_report(app: AppHandle, name: String) -> Result<(), String> {
+    let path = crash::report_path(&name)?;
+    let endpoint = {
+        let state = app.state::<AppState>();
+        let manifest = state.manifest.lock().unwrap();
+        manifest.as_ref().and_then(|m| m.crash_report_url.clone())
+    };
+    let Some(endpoint) = endpoint else {
+        return Err("No crash report endpoint configured".to_string());
+    };
+    crash::upload(&path, &endpoint).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_recent_logs(limit: Option<usize>, min_level: Option<String>) -> Vec<logger::LogEntry> {
+    logger::get_recent_logs(limit, min_level.as_deref())
+}
+
+#[tauri::command]
+async fn export_diagnostics(app: AppHandle, path: String) -> Result<String, String> {
+    let (config, manifest) = {
+        let state = app.state::<AppState>();
+        let config = state.config.lock().unwrap().clone();
+        let manifest = state.manifest.lock().unwrap().clone();
+        (config, manifest)
+    };
+    diagnostics::export(path.into(), config, manifest, MANIFEST_URL)
+        .await
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn get_config(app: AppHandle) -> LauncherConfig {
     let state = app.state::<AppState>();
@@ -153,54 +280,165 @@ fn get_config(app: AppHandle) -> LauncherConfig {
     config
 }
 
+/// Returns the config as saved, after validation may have corrected it
 #[tauri::command]
-fn save_config(app: AppHandle, config: LauncherConfig) {
+fn save_config(app: AppHandle, mut config: LauncherConfig) -> Result<LauncherConfig, String> {
     let state = app.state::<AppState>();
+    let manifest = state.manifest.lock().unwrap().clone();
+    config.validate(manifest.as_ref());
     *state.config.lock().unwrap() = config.clone();
-    config.save();
-    
-    // Apply autostart if needed (requires tauri-plugin-autostart, strict user constraint "No unused dependencies" -> maybe registry?)
-    // User asked for "Windows auto-start (no admin required)".
-    // Standard way is Registry HKCU\Software\Microsoft\Windows\CurrentVersion\Run.
-    // Or tauri-plugin-autostart.
-    // I'll skip implementation details for brevity here but stash it in TODO or if I added the plugin.
-    // I didn't add the plugin. I can use `rege` crate or simple command.
+    config.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    log_err!(autostart::reconcile(config.launch_at_startup), "apply launch at startup");
+    Ok(config)
+}
+
+/// Check a ganhrong:// link against the current manifest and carry it out
+fn dispatch_deep_link(app: &AppHandle, link: &deep_link::DeepLink) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let manifest = state.manifest.lock().unwrap().clone();
+    let Some(m) = manifest else {
+        return Err("Cannot verify version (Offline or No Manifest)".to_string());
+    };
+    let settings = { state.config.lock().unwrap().launch.clone() };
+    link.validate(&m, &settings).map_err(|e| e.to_string())?;
+
+    match link {
+        deep_link::DeepLink::Launch { profile } => start_game(app, profile.as_deref(), None),
+        deep_link::DeepLink::Join { server, profile } => start_game(app, profile.as_deref(), Some(server)),
+        deep_link::DeepLink::News { id } => app.emit("open-news", id).map_err(|e| e.to_string()),
+    }
+}
+
+/// Links arrive on the command line (cold start) or from a second launch. Right after startup the
+/// manifest may not be loaded yet, so wait for it a little before giving up.
+fn handle_deep_link(app: &AppHandle, url: &str) {
+    let link = match deep_link::parse(url) {
+        Ok(link) => link,
+        Err(e) => {
+            log::warn!("Ignoring link {}: {}", url, e);
+            log_err!(app.emit("deep-link-error", e.to_string()), "emit deep-link-error");
+            return;
+        }
+    };
+    log::info!("Opening link {:?}", link);
+    tray::show_main_window(app);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for _ in 0..DEEP_LINK_MANIFEST_WAIT_SECS {
+            if app.state::<AppState>().manifest.lock().unwrap().is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        if let Err(e) = dispatch_deep_link(&app, &link) {
+            log::warn!("Link {:?} failed: {}", link, e);
+            log_err!(app.emit("deep-link-error", e), "emit deep-link-error");
+        }
+    });
+}
+
+/// Command line of a later launch, passed on by the single-instance lock
+fn handle_forwarded_args(app: &AppHandle, args: &[String]) {
+    log::info!("Launcher started again with {:?}", args.get(1..).unwrap_or_default());
+    if let Some(url) = deep_link::find_in_args(args) {
+        handle_deep_link(app, url);
+        return;
+    }
+    // Login autostart while we're already running, stay where we are
+    if args.iter().any(|a| a == autostart::MINIMIZED_ARG) {
+        return;
+    }
+    tray::show_main_window(app);
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logger::init();
+    let args: Vec<String> = std::env::args().collect();
+
+    // One launcher per data dir: a second launch hands its args over and exits
+    let instance_lock = match single_instance::acquire() {
+        Ok(single_instance::Instance::Primary(lock)) => Some(lock),
+        Ok(single_instance::Instance::Secondary) => match single_instance::forward_or_take_over(&args) {
+            Ok(Some(lock)) => {
+                log::info!("Previous launcher exited, starting");
+                Some(lock)
+            }
+            Ok(None) => {
+                log::info!("Launcher is already running, passed the arguments on");
+                return;
+            }
+            Err(e) => {
+                log::error!("Launcher is already running but didn't respond: {}", e);
+                return;
+            }
+        },
+        Err(e) => {
+            log::warn!("Single instance check failed, starting anyway: {}", e);
+            None
+        }
+    };
+    log::info!("Launcher {} starting", env!("CARGO_PKG_VERSION"));
+    // --minimized (autostart): tray only, the window stays hidden until opened from the tray
+    let minimized = args.iter().any(|a| a == autostart::MINIMIZED_ARG);
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .setup(|app| {
+        .setup(move |app| {
+            self_update::init(app.package_info().version.to_string());
             let state = AppState::new();
-            // Load config
-            *state.config.lock().unwrap() = crate::config::LauncherConfig::load();
+            // Load config, checked against the last manifest we saw
+            let mut config = crate::config::LauncherConfig::load();
+            let cached = manifest::load_cached_manifest();
+            if !config.validate(cached.as_ref()).is_empty() {
+                log_err!(config.save(), "save corrected config");
+            }
+            log_err!(autostart::reconcile(config.launch_at_startup), "apply launch at startup");
+            *state.config.lock().unwrap() = config;
             app.manage(state);
             
             // Tray
             tray::create_tray(app.handle())?;
+            if !minimized {
+                tray::show_main_window(app.handle());
+            }
+
+            if let Some(lock) = instance_lock {
+                let handle = app.handle().clone();
+                lock.listen(move |args| handle_forwarded_args(&handle, &args));
+            }
+            log_err!(deep_link::register(), "register ganhrong:// links");
+            if let Some(url) = deep_link::find_in_args(&args) {
+                handle_deep_link(app.handle(), url);
+            }
+
+            // Recover from Offline automatically once the CDN is reachable again
+            connectivity::spawn_watcher(app.handle().clone(), MANIFEST_URL);
             
-            // Polling Timer
-            let handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                loop {
-                    tokio::time::sleep(std::time::Duration::from_secs(600)).await; // 10 mins
-                    // Fetch silently
-                     let _ = manifest::fetch_manifest(MANIFEST_URL).await;
-                     // Update state if new version...
-                     // (Simplification: fetch_manifest caches it. 
-                     // Frontend should poll `get_manifest` periodically or we emit event)
-                     let _ = handle.emit("manifest-updated", ());
-                }
-            });
+            // Update watcher (manifest polling + status)
+            poller::spawn(app.handle().clone(), MANIFEST_URL);
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_manifest, 
-            start_download, 
+            get_connectivity,
+            get_status,
+            check_for_updates,
+            set_update_polling,
+            start_download,
+            start_preload, 
             get_local_version, 
             launch_game,
+            get_launch_profiles,
+            get_running_game,
+            get_play_stats,
+            list_game_logs,
+            read_game_log,
+            upload_crash_report,
+            get_recent_logs,
+            export_diagnostics,
             get_config,
             save_config
         ])
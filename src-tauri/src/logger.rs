@@ -0,0 +1,165 @@
+use chrono::Local;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use crate::paths;
+
+const LOG_FILE: &str = "launcher.log";
+/// Rotate launcher.log -> launcher.1.log ... once it reaches this size
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES: u32 = 3;
+/// Entries kept in memory for `get_recent_logs`
+const RECENT_CAPACITY: usize = 1000;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String, // downloader, manifest, game, tray...
+    pub message: String,
+}
+
+struct LogFile {
+    file: Option<File>,
+    written: u64,
+}
+
+struct LauncherLogger {
+    level: LevelFilter,
+    dir: PathBuf,
+    out: Mutex<LogFile>,
+    recent: Mutex<VecDeque<LogEntry>>,
+}
+
+static LOGGER: OnceLock<LauncherLogger> = OnceLock::new();
+
+/// Log the error of a Result we don't otherwise act on
+macro_rules! log_err {
+    ($result:expr, $($context:tt)+) => {
+        if let Err(e) = $result {
+            log::warn!("{}: {}", format_args!($($context)+), e);
+        }
+    };
+}
+pub(crate) use log_err;
+
+/// "tauri_app_lib::launcher_core::download" -> "launcher_core::download", the crate root is "launcher"
+fn short_target(target: &str) -> String {
+    match target.strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::")) {
+        Some(rest) => rest.to_string(),
+        None if target == env!("CARGO_CRATE_NAME") => "launcher".to_string(),
+        None => target.to_string(),
+    }
+}
+
+impl LauncherLogger {
+    fn open(&self) -> LogFile {
+        let _ = fs::create_dir_all(&self.dir);
+        let path = self.dir.join(LOG_FILE);
+        let file = OpenOptions::new().create(true).append(true).open(&path).ok();
+        let written = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        LogFile { file, written }
+    }
+
+    fn rotate(&self) {
+        let rotated = |n: u32| self.dir.join(format!("launcher.{}.log", n));
+        let _ = fs::remove_file(rotated(MAX_ROTATED_FILES));
+        for n in (1..MAX_ROTATED_FILES).rev() {
+            let _ = fs::rename(rotated(n), rotated(n + 1));
+        }
+        let _ = fs::rename(self.dir.join(LOG_FILE), rotated(1));
+    }
+}
+
+impl Log for LauncherLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Dependencies (hyper, reqwest...) only get through with warnings and errors
+        let ours = metadata.target().starts_with(env!("CARGO_CRATE_NAME"));
+        metadata.level() <= self.level && (ours || metadata.level() <= Level::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = LogEntry {
+            timestamp: Local::now().to_rfc3339(),
+            level: record.level().to_string(),
+            target: short_target(record.target()),
+            message: record.args().to_string(),
+        };
+        let line = format!("{} {:<5} [{}] {}\n", entry.timestamp, entry.level, entry.target, entry.message);
+
+        {
+            let mut out = self.out.lock().unwrap();
+            if out.written + line.len() as u64 > MAX_LOG_SIZE {
+                out.file = None;
+                self.rotate();
+                *out = self.open();
+            }
+            if let Some(file) = out.file.as_mut() {
+                if file.write_all(line.as_bytes()).is_ok() {
+                    out.written += line.len() as u64;
+                }
+            }
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(entry);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.out.lock().unwrap().file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Install the launcher logger. Level comes from `GANHRONG_LOG` (error/warn/info/debug/trace).
+pub fn init() {
+    let default = if cfg!(debug_assertions) { LevelFilter::Debug } else { LevelFilter::Info };
+    let level = std::env::var("GANHRONG_LOG")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default);
+
+    let logger = LOGGER.get_or_init(|| {
+        let logger = LauncherLogger {
+            level,
+            dir: paths::get_logs_dir(),
+            out: Mutex::new(LogFile { file: None, written: 0 }),
+            recent: Mutex::new(VecDeque::new()),
+        };
+        *logger.out.lock().unwrap() = logger.open();
+        logger
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/// Newest last. `min_level` filters like the file level ("warn" returns warn + error).
+pub fn get_recent_logs(limit: Option<usize>, min_level: Option<&str>) -> Vec<LogEntry> {
+    let Some(logger) = LOGGER.get() else {
+        return Vec::new();
+    };
+    let min_level: Option<Level> = min_level.and_then(|l| l.parse().ok());
+    let recent = logger.recent.lock().unwrap();
+    let filtered: Vec<LogEntry> = recent
+        .iter()
+        .filter(|e| match (min_level, e.level.parse::<Level>()) {
+            (Some(min), Ok(level)) => level <= min,
+            _ => true,
+        })
+        .cloned()
+        .collect();
+    let skip = filtered.len().saturating_sub(limit.unwrap_or(filtered.len()));
+    filtered.into_iter().skip(skip).collect()
+}
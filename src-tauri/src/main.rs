@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(code) = tauri_app_lib::cli::run(&args) {
+        std::process::exit(code);
+    }
     tauri_app_lib::run()
 }
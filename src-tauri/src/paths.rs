@@ -1,8 +1,98 @@
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
 
-/// Get the base directory where the app is running from
-/// This returns the directory containing the executable
-pub fn get_app_dir() -> PathBuf {
+/// Folder name under the platform data/config/cache dirs in installed mode
+const APP_DIR_NAME: &str = "ganhrong-launcher";
+/// Dropping this file next to the executable keeps everything in that folder (USB sticks, shared PCs)
+pub const PORTABLE_MARKER: &str = "portable.txt";
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum PathMode {
+    Portable,
+    Installed,
+}
+
+/// Every location the launcher reads or writes. Resolved once at startup.
+#[derive(Serialize, Clone, Debug)]
+pub struct LauncherPaths {
+    pub mode: PathMode,
+    pub data_dir: PathBuf,   // game/, play history
+    pub config_dir: PathBuf, // config.json
+    pub cache_dir: PathBuf,  // Manifest cache, downloads, crash reports
+    pub logs_dir: PathBuf,
+}
+
+impl LauncherPaths {
+    /// Everything under one folder, the layout the launcher has always used next to the exe
+    pub fn portable(root: &Path) -> Self {
+        Self {
+            mode: PathMode::Portable,
+            data_dir: root.to_path_buf(),
+            config_dir: root.to_path_buf(),
+            cache_dir: root.join("cache"),
+            logs_dir: root.join("logs"),
+        }
+    }
+
+    /// Per-user platform dirs. None if we can't work out the home folder.
+    pub fn installed() -> Option<Self> {
+        let (data, config, cache) = platform_dirs()?;
+        let data_dir = data.join(APP_DIR_NAME);
+        Some(Self {
+            mode: PathMode::Installed,
+            logs_dir: data_dir.join("logs"),
+            data_dir,
+            config_dir: config.join(APP_DIR_NAME),
+            cache_dir: cache.join(APP_DIR_NAME),
+        })
+    }
+
+    /// Portable if the marker file is next to the exe, or if there's already a game/ folder there
+    /// from before installed mode existed, so existing players keep their install and settings.
+    pub fn detect() -> Self {
+        let exe_dir = exe_dir();
+        if exe_dir.join(PORTABLE_MARKER).exists() || exe_dir.join("game").join("version.txt").exists() {
+            return Self::portable(&exe_dir);
+        }
+        Self::installed().unwrap_or_else(|| Self::portable(&exe_dir))
+    }
+
+    pub fn game_dir(&self) -> PathBuf {
+        self.data_dir.join("game")
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("config.json")
+    }
+}
+
+static DETECTED: OnceLock<LauncherPaths> = OnceLock::new();
+static OVERRIDE: RwLock<Option<LauncherPaths>> = RwLock::new(None);
+
+/// Use `paths` instead of the detected locations. Tests point this at a temp dir.
+pub fn set_override(paths: Option<LauncherPaths>) {
+    *OVERRIDE.write().unwrap() = paths;
+}
+
+/// Portable layout under `root`, or back to the detected locations with None
+pub fn set_root_override(root: Option<PathBuf>) {
+    set_override(root.map(|r| LauncherPaths::portable(&r)));
+}
+
+pub fn is_overridden() -> bool {
+    OVERRIDE.read().unwrap().is_some()
+}
+
+pub fn current() -> LauncherPaths {
+    if let Some(paths) = OVERRIDE.read().unwrap().clone() {
+        return paths;
+    }
+    DETECTED.get_or_init(LauncherPaths::detect).clone()
+}
+
+/// Directory containing the executable
+fn exe_dir() -> PathBuf {
     // Try to get the directory of the current executable
     if let Ok(exe_path) = std::env::current_exe() {
         if let Some(parent) = exe_path.parent() {
@@ -13,12 +103,58 @@ pub fn get_app_dir() -> PathBuf {
     std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
 }
 
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// (data, config, cache) base dirs for this platform
+#[cfg(target_os = "windows")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    let local = env_dir("LOCALAPPDATA")?;
+    let roaming = env_dir("APPDATA").unwrap_or_else(|| local.clone());
+    Some((local.clone(), roaming, local.join("Cache")))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    let library = env_dir("HOME")?.join("Library");
+    let support = library.join("Application Support");
+    Some((support.clone(), support, library.join("Caches")))
+}
+
+/// XDG base directories, with the spec's defaults when the variables aren't set
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn platform_dirs() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    let home = env_dir("HOME");
+    let xdg = |var: &str, default: &str| env_dir(var).or_else(|| home.as_ref().map(|h| h.join(default)));
+    Some((
+        xdg("XDG_DATA_HOME", ".local/share")?,
+        xdg("XDG_CONFIG_HOME", ".config")?,
+        xdg("XDG_CACHE_HOME", ".cache")?,
+    ))
+}
+
+/// Get the base directory for launcher data (game/, play history)
+pub fn get_data_dir() -> PathBuf {
+    current().data_dir
+}
+
 /// Get path to the game directory
 pub fn get_game_dir() -> PathBuf {
-    get_app_dir().join("game")
+    current().game_dir()
 }
 
 /// Get path to the cache directory
 pub fn get_cache_dir() -> PathBuf {
-    get_app_dir().join("cache")
+    current().cache_dir
+}
+
+/// Get path to the logs directory (launcher and game logs)
+pub fn get_logs_dir() -> PathBuf {
+    current().logs_dir
+}
+
+/// Get path to the launcher settings file
+pub fn get_config_path() -> PathBuf {
+    current().config_file()
 }
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use crate::config;
+use crate::paths;
+use crate::supervisor::GameExit;
+
+/// One play session, as recorded when the game exits
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Session {
+    pub started_at: String,
+    pub ended_at: String,
+    pub duration_secs: u64,
+    pub version: String,
+    pub exit_code: Option<i32>,
+    /// The launcher exited with the game still running (CloseBehavior::Exit), so only the start is known
+    #[serde(default)]
+    pub unfinished: bool,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct VersionStats {
+    pub total_secs: u64,
+    pub sessions: usize,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct PlayStats {
+    pub total_secs: u64,
+    pub sessions: usize,
+    pub last_played: Option<String>,
+    pub per_version: BTreeMap<String, VersionStats>,
+}
+
+fn history_path() -> PathBuf {
+    paths::get_data_dir().join("play_history.json")
+}
+
+/// Saved sessions. A history that doesn't parse is moved aside to play_history.json.bad-<time>
+/// so the next recorded session can't overwrite it.
+pub fn load_sessions() -> Vec<Session> {
+    let path = history_path();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    match serde_json::from_str(&content) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            let aside = path.with_file_name(format!("play_history.json.bad-{}", Local::now().format("%Y%m%d-%H%M%S")));
+            log::warn!("Play history is corrupt ({}), moving it to {}", e, aside.display());
+            if let Err(e) = fs::rename(&path, &aside) {
+                log::error!("Failed to move corrupt play history aside: {}", e);
+            }
+            Vec::new()
+        }
+    }
+}
+
+/// Add one session to play_history.json
+pub fn append_session(session: Session) -> io::Result<()> {
+    let path = history_path();
+    let mut sessions = load_sessions();
+    // A non-empty file that gave no sessions is a corrupt one load_sessions couldn't move aside, don't replace it
+    if sessions.is_empty() && fs::metadata(&path).is_ok_and(|m| m.len() > 2) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "play history is corrupt and couldn't be moved aside"));
+    }
+    sessions.push(session);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    config::write_atomic(&path, serde_json::to_string_pretty(&sessions)?.as_bytes())
+}
+
+pub fn record_session(exit: &GameExit) -> io::Result<()> {
+    append_session(Session {
+        started_at: exit.started_at.clone(),
+        ended_at: exit.ended_at.clone(),
+        duration_secs: exit.duration_secs,
+        version: exit.version.clone(),
+        exit_code: exit.exit_code,
+        unfinished: false,
+    })
+}
+
+/// With CloseBehavior::Exit nobody is left to see the game exit, so record what we know now
+pub fn record_unfinished(version: &str, started_at: &str) -> io::Result<()> {
+    append_session(Session {
+        started_at: started_at.to_string(),
+        ended_at: started_at.to_string(),
+        duration_secs: 0,
+        version: version.to_string(),
+        exit_code: None,
+        unfinished: true,
+    })
+}
+
+pub fn compute_stats(sessions: &[Session]) -> PlayStats {
+    let mut stats = PlayStats::default();
+    for s in sessions {
+        stats.total_secs += s.duration_secs;
+        stats.sessions += 1;
+        let v = stats.per_version.entry(s.version.clone()).or_default();
+        v.total_secs += s.duration_secs;
+        v.sessions += 1;
+    }
+    stats.last_played = sessions
+        .iter()
+        .filter_map(|s| DateTime::parse_from_rfc3339(&s.ended_at).ok().map(|t| (t, &s.ended_at)))
+        .max_by_key(|(t, _)| *t)
+        .map(|(_, ended_at)| ended_at.clone());
+    stats
+}
+
+pub fn get_play_stats() -> PlayStats {
+    compute_stats(&load_sessions())
+}
@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+use crate::auto_update;
+use crate::connectivity::{self, Connectivity};
+use crate::launcher_core::compat;
+use crate::launcher_core::game;
+use crate::launcher_core::manifest::{self, GameManifest, ManifestFetch};
+use crate::preload;
+use crate::state::{AppState, GameStatus};
+use crate::logger::log_err;
+
+/// Lets commands pause the update watcher or wake it up early
+pub struct PollerControl {
+    paused: AtomicBool,
+    wake: Notify,
+}
+
+impl PollerControl {
+    pub fn new() -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            wake: Notify::new(),
+        }
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Run a check now, even while paused
+    pub fn trigger(&self) {
+        self.wake.notify_one();
+    }
+}
+
+/// Status the launcher should show for this manifest and installed version
+pub fn compute_status(manifest: &GameManifest, local_version: &str) -> GameStatus {
+    if compat::is_outdated(manifest) {
+        return GameStatus::LauncherOutdated;
+    }
+    match manifest.server_status.as_deref() {
+        Some("maintenance") | Some("closed") => GameStatus::Maintenance,
+        _ if local_version == "0.0.0" => GameStatus::Missing,
+        _ if local_version != manifest.latest_version => GameStatus::UpdateAvailable,
+        _ => GameStatus::ReadyToPlay,
+    }
+}
+
+/// Recompute GameStatus from the current manifest, leaving in-progress and offline states alone
+pub fn refresh_status(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let manifest = { state.manifest.lock().unwrap().clone() };
+    let Some(m) = manifest else {
+        return;
+    };
+    let next = compute_status(&m, &game::get_local_version());
+
+    let changed = {
+        let mut status = state.status.lock().unwrap();
+        match *status {
+            ref current if !current.is_derived() || *current == next => false,
+            _ => {
+                *status = next.clone();
+                true
+            }
+        }
+    };
+    if changed {
+        log_err!(app.emit("status-changed", next), "emit status-changed");
+    }
+}
+
+/// One conditional manifest fetch, updating AppState and notifying the frontend on change
+pub async fn poll_once(app: &AppHandle, url: &str) {
+    let failed = match manifest::fetch_manifest_if_changed(url).await {
+        Ok(ManifestFetch::Changed { manifest, diff }) => {
+            connectivity::report(app, Connectivity::Online);
+            *app.state::<AppState>().manifest.lock().unwrap() = Some(*manifest);
+            refresh_status(app);
+            log_err!(app.emit("manifest-updated", diff), "emit manifest-updated");
+            None
+        }
+        Ok(ManifestFetch::Unchanged) => {
+            connectivity::report(app, Connectivity::Online);
+            {
+                let state = app.state::<AppState>();
+                let mut current = state.manifest.lock().unwrap();
+                if current.is_none() {
+                    *current = manifest::load_cached_manifest();
+                }
+            }
+            refresh_status(app);
+            None
+        }
+        Err(e) => {
+            log::warn!("Manifest poll failed: {}", e);
+            Some(connectivity::classify_error(e.as_ref()))
+        }
+    };
+    if let Some(classified) = failed {
+        connectivity::report_failure(app, url, classified).await;
+    }
+}
+
+/// Background update watcher. Interval comes from `LauncherConfig`, re-read every cycle.
+pub fn spawn(app: AppHandle, url: &'static str) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = {
+                let mins = app.state::<AppState>().config.lock().unwrap().update_check_interval_mins;
+                Duration::from_secs(mins.max(1) * 60)
+            };
+
+            let triggered = {
+                let state = app.state::<AppState>();
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => false,
+                    _ = state.poller.wake.notified() => true,
+                }
+            };
+
+            if !triggered && app.state::<AppState>().poller.is_paused() {
+                continue;
+            }
+            poll_once(&app, url).await;
+            crate::self_update::maybe_run(&app);
+            auto_update::maybe_run(&app);
+            preload::maybe_run(&app);
+        }
+    });
+}
@@ -0,0 +1,124 @@
+use chrono::Utc;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::auto_update;
+use crate::downloader::{self, Target, TauriProgress};
+use crate::launcher_core::{compat, download};
+use crate::launcher_core::game;
+use crate::launcher_core::manifest::{GameManifest, PreloadInfo};
+use crate::state::AppState;
+use crate::supervisor;
+use crate::logger::log_err;
+
+pub fn is_unlocked(preload: &PreloadInfo, now: i64) -> bool {
+    now >= preload.unlock_at
+}
+
+/// What the preload watcher should do next
+#[derive(Debug, PartialEq)]
+pub enum PreloadAction {
+    None,
+    /// Download the upcoming version into staging
+    Stage(PreloadInfo),
+    /// The manifest moved on to a version we preloaded, install it from staging
+    Install,
+}
+
+/// Decide from the manifest, the installed version and what's in cache/.
+/// `stage_allowed` is the auto-update policy's say, or true when the player asked for the preload.
+pub fn next_action(m: &GameManifest, local: &str, now: i64, stage_allowed: bool) -> PreloadAction {
+    // Release day. The preload block may be gone by now, the staged archive is what counts.
+    if local != m.latest_version && download::is_preloaded(&m.latest_version, &m.checksum) {
+        let locked = m.preload.as_ref().is_some_and(|p| p.version == m.latest_version && !is_unlocked(p, now));
+        return if locked { PreloadAction::None } else { PreloadAction::Install };
+    }
+
+    match &m.preload {
+        Some(preload)
+            if stage_allowed
+                && preload.version != m.latest_version
+                && preload.version != local
+                && !download::is_preloaded(&preload.version, &preload.checksum) =>
+        {
+            PreloadAction::Stage(preload.clone())
+        }
+        _ => PreloadAction::None,
+    }
+}
+
+/// Stage the manifest's preload archive in cache/, and once it's released install it
+/// from there without downloading again. Called by the update watcher after each poll.
+pub fn maybe_run(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Some(m) = state.manifest.lock().unwrap().clone() else {
+        return;
+    };
+
+    let mut keep = vec![m.latest_version.as_str()];
+    keep.extend(m.preload.as_ref().map(|p| p.version.as_str()));
+    download::prune_staged(&keep);
+
+    let policy = { state.config.lock().unwrap().auto_update.clone() };
+    let local = game::get_local_version();
+    match next_action(&m, &local, Utc::now().timestamp(), auto_update::background_download_allowed(&policy)) {
+        PreloadAction::Install => {
+            let idle = supervisor::running_instance(app).is_none();
+            let settled = state.status.lock().unwrap().is_derived();
+            if idle && settled && !compat::is_outdated(&m) {
+                let _ = downloader::spawn_install(app.clone(), m); // Busy: next poll
+            }
+        }
+        PreloadAction::Stage(preload) => {
+            stage(app, preload);
+        }
+        PreloadAction::None => {}
+    }
+}
+
+/// Player asked to preload now, whatever the auto-update mode or time window
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let Some(m) = state.manifest.lock().unwrap().clone() else {
+        return Err("No manifest loaded".to_string());
+    };
+    if m.preload.is_none() {
+        return Err("No preload available".to_string());
+    }
+    match next_action(&m, &game::get_local_version(), Utc::now().timestamp(), true) {
+        PreloadAction::Stage(preload) => {
+            if stage(app, preload) { Ok(()) } else { Err(downloader::BUSY_ERROR.to_string()) }
+        }
+        // Already staged, installed or released
+        _ => Ok(()),
+    }
+}
+
+/// Download into staging in the background, false if that archive is already being downloaded
+fn stage(app: &AppHandle, preload: PreloadInfo) -> bool {
+    // Only the preload's own archive, a manual update of the current version can run alongside
+    let targets = [Target::Archive(preload.version.clone())];
+    if !downloader::try_begin(app, &targets) {
+        return false;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let res = download::download_archive(
+            &preload.archive,
+            &preload.checksum,
+            &preload.version,
+            &TauriProgress::new(&app, "preload-progress"),
+        ).await;
+        if res.is_ok() {
+            log_err!(download::mark_preloaded(&preload.version), "mark preload {}", preload.version);
+        }
+        downloader::end(&app, &targets);
+        match res {
+            Ok(_) => log_err!(app.emit("preload-ready", preload.version.clone()), "emit preload-ready"),
+            Err(e) => {
+                log::error!("Preload of {} failed: {}", preload.version, e);
+                log_err!(app.emit("preload-error", e.to_string()), "emit preload-error");
+            }
+        }
+    });
+    true
+}
@@ -0,0 +1,320 @@
+use base64::Engine;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::launcher_core::download;
+use crate::launcher_core::manifest::{self, FileHash, FileList, GameManifest, ManifestPresentation, CURRENT_SCHEMA_VERSION};
+
+const USAGE: &str = "Usage: release --build-dir <dir> --version <x.y.z> --base-url <url> [--out <dir>] [--name <slug>]
+               [--format zip|tar.zst] [--manifest <current manifest.json>]
+               [--previous <previous .files.json>] [--signing-key <hex seed file>]";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "zip" => Some(Self::Zip),
+            "tar.zst" => Some(Self::TarZst),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::TarZst => "tar.zst",
+        }
+    }
+}
+
+pub struct ReleaseOptions {
+    pub build_dir: PathBuf,
+    pub version: String,
+    pub out_dir: PathBuf,
+    pub name: String,          // Archive prefix, e.g. "ganhrong" -> ganhrong-v1.0.0.zip
+    pub base_url: String,      // Where the archives will be uploaded
+    pub format: ArchiveFormat,
+    pub manifest: Option<PathBuf>,  // Current manifest, news/backgrounds etc. are kept
+    pub previous: Option<PathBuf>,  // File list of the previous release, to build a delta
+    pub signing_key: Option<PathBuf>,
+}
+
+/// Files that changed since a previous release, for patching instead of a full download
+#[derive(Serialize, Debug)]
+pub struct DeltaInfo {
+    pub from: String,
+    pub to: String,
+    pub archive: String,
+    pub checksum: String,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// What the release tool wrote, printed as JSON when it's done
+#[derive(Serialize, Debug)]
+pub struct ReleaseSummary {
+    pub version: String,
+    pub archive: PathBuf,
+    pub checksum: String,
+    pub file_list: PathBuf,
+    pub file_count: usize,
+    pub delta: Option<DeltaInfo>,
+    pub manifest: PathBuf,
+    pub signature: Option<PathBuf>,
+}
+
+/// Every file under `dir`, sorted, as '/' separated relative paths.
+/// version.txt is written by the launcher on install and never shipped.
+fn collect_files(dir: &Path) -> io::Result<Vec<String>> {
+    fn walk(root: &Path, dir: &Path, out: &mut Vec<String>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                walk(root, &path, out)?;
+            } else if let Ok(rel) = path.strip_prefix(root) {
+                out.push(rel.components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"));
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    walk(dir, dir, &mut files)?;
+    files.retain(|f| f != "version.txt");
+    files.sort();
+    Ok(files)
+}
+
+fn hash_files(build_dir: &Path, version: &str, files: &[String]) -> io::Result<FileList> {
+    let files = files.iter()
+        .map(|rel| {
+            let path = build_dir.join(rel);
+            Ok(FileHash {
+                path: rel.clone(),
+                size: fs::metadata(&path)?.len(),
+                sha256: download::sha256_file(&path)?,
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(FileList { version: version.to_string(), files })
+}
+
+fn write_archive(build_dir: &Path, files: &[String], dest: &Path, format: ArchiveFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let out = File::create(dest)?;
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = ZipWriter::new(out);
+            let options = FileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .large_file(true);
+            for rel in files {
+                zip.start_file(rel.as_str(), options)?;
+                io::copy(&mut File::open(build_dir.join(rel))?, &mut zip)?;
+            }
+            zip.finish()?;
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::Encoder::new(out, 19)?;
+            let mut tar = tar::Builder::new(encoder);
+            for rel in files {
+                tar.append_path_with_name(build_dir.join(rel), rel)?;
+            }
+            tar.into_inner()?.finish()?.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Changed or new files, and files that are gone, compared to the previous release
+fn diff_file_lists(previous: &FileList, current: &FileList) -> (Vec<String>, Vec<String>) {
+    let old: HashMap<&str, &str> = previous.files.iter().map(|f| (f.path.as_str(), f.sha256.as_str())).collect();
+    let new: HashMap<&str, &str> = current.files.iter().map(|f| (f.path.as_str(), f.sha256.as_str())).collect();
+    let changed = current.files.iter()
+        .filter(|f| old.get(f.path.as_str()) != Some(&f.sha256.as_str()))
+        .map(|f| f.path.clone())
+        .collect();
+    let removed = previous.files.iter()
+        .filter(|f| !new.contains_key(f.path.as_str()))
+        .map(|f| f.path.clone())
+        .collect();
+    (changed, removed)
+}
+
+fn load_signing_key(path: &Path) -> Result<SigningKey, Box<dyn std::error::Error>> {
+    let seed: [u8; 32] = hex::decode(fs::read_to_string(path)?.trim())?
+        .try_into()
+        .map_err(|_| "Signing key must be a 32 byte hex seed")?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+fn url_join(base: &str, name: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), name)
+}
+
+/// The current manifest with this release as the latest version
+fn build_manifest(options: &ReleaseOptions, archive_name: &str, checksum: &str, file_list_name: &str) -> Result<GameManifest, Box<dyn std::error::Error>> {
+    let mut m = match &options.manifest {
+        Some(path) => manifest::parse_manifest(&fs::read(path)?)?,
+        None => GameManifest {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            game_name: options.name.clone(),
+            game_exe: None,
+            latest_version: String::new(),
+            game_zip: String::new(),
+            checksum: String::new(),
+            file_list: None,
+            server_status: Some("online".to_string()),
+            min_launcher_version: None,
+            presentation: ManifestPresentation::default(),
+            preload: None,
+            launch: None,
+            crash_report_url: None,
+            launcher_update: None,
+        },
+    };
+    m.schema_version = CURRENT_SCHEMA_VERSION;
+    m.latest_version = options.version.clone();
+    m.game_zip = url_join(&options.base_url, archive_name);
+    m.checksum = format!("sha256:{}", checksum);
+    m.file_list = Some(url_join(&options.base_url, file_list_name));
+    // This release supersedes any preload of the same version
+    if m.preload.as_ref().is_some_and(|p| p.version == options.version) {
+        m.preload = None;
+    }
+    Ok(m)
+}
+
+/// Archive a build folder, hash it, diff it against the previous release and write the manifest
+pub fn build_release(options: &ReleaseOptions) -> Result<ReleaseSummary, Box<dyn std::error::Error>> {
+    semver::Version::parse(&options.version)
+        .map_err(|e| format!("'{}' is not a semantic version: {}", options.version, e))?;
+    fs::create_dir_all(&options.out_dir)?;
+
+    let files = collect_files(&options.build_dir)?;
+    if files.is_empty() {
+        return Err(format!("{} has no files", options.build_dir.display()).into());
+    }
+    let file_list = hash_files(&options.build_dir, &options.version, &files)?;
+
+    let stem = format!("{}-v{}", options.name, options.version);
+    let file_list_name = format!("{}.files.json", stem);
+    let file_list_path = options.out_dir.join(&file_list_name);
+    fs::write(&file_list_path, serde_json::to_string_pretty(&file_list)?)?;
+
+    let archive_name = format!("{}.{}", stem, options.format.extension());
+    let archive_path = options.out_dir.join(&archive_name);
+    write_archive(&options.build_dir, &files, &archive_path, options.format)?;
+    let checksum = download::sha256_file(&archive_path)?;
+
+    let delta = match &options.previous {
+        Some(path) => {
+            let previous: FileList = serde_json::from_slice(&fs::read(path)?)?;
+            let (changed, removed) = diff_file_lists(&previous, &file_list);
+            let delta_stem = format!("{}-v{}-to-v{}", options.name, previous.version, options.version);
+            let name = format!("{}.{}", delta_stem, options.format.extension());
+            let delta_path = options.out_dir.join(&name);
+            write_archive(&options.build_dir, &changed, &delta_path, options.format)?;
+            let info = DeltaInfo {
+                from: previous.version,
+                to: options.version.clone(),
+                checksum: format!("sha256:{}", download::sha256_file(&delta_path)?),
+                archive: url_join(&options.base_url, &name),
+                changed,
+                removed,
+            };
+            fs::write(options.out_dir.join(format!("{}.delta.json", delta_stem)), serde_json::to_string_pretty(&info)?)?;
+            Some(info)
+        }
+        None => None,
+    };
+
+    let m = build_manifest(options, &archive_name, &checksum, &file_list_name)?;
+    let manifest_json = serde_json::to_string_pretty(&m)?;
+    let manifest_path = options.out_dir.join("manifest.json");
+    fs::write(&manifest_path, &manifest_json)?;
+
+    // Detached base64 Ed25519 signature over the exact manifest bytes, same scheme as launcher updates
+    let signature = match &options.signing_key {
+        Some(key_path) => {
+            let key = load_signing_key(key_path)?;
+            let sig = key.sign(manifest_json.as_bytes());
+            let sig_path = options.out_dir.join("manifest.json.sig");
+            fs::write(&sig_path, base64::engine::general_purpose::STANDARD.encode(sig.to_bytes()))?;
+            Some(sig_path)
+        }
+        None => None,
+    };
+
+    Ok(ReleaseSummary {
+        version: options.version.clone(),
+        archive: archive_path,
+        checksum: format!("sha256:{}", checksum),
+        file_list: file_list_path,
+        file_count: file_list.files.len(),
+        delta,
+        manifest: manifest_path,
+        signature,
+    })
+}
+
+fn parse_args(args: &[String]) -> Result<ReleaseOptions, String> {
+    let mut values: HashMap<&str, &str> = HashMap::new();
+    let mut iter = args.iter().skip(1);
+    while let Some(flag) = iter.next() {
+        let Some(key) = flag.strip_prefix("--") else {
+            return Err(format!("Unexpected argument '{}'", flag));
+        };
+        let value = iter.next().ok_or_else(|| format!("--{} needs a value", key))?;
+        values.insert(key, value);
+    }
+
+    let required = |key: &str| values.get(key).map(|v| v.to_string()).ok_or_else(|| format!("--{} is required", key));
+    let format = match values.get("format") {
+        Some(f) => ArchiveFormat::parse(f).ok_or_else(|| format!("Unknown format '{}'", f))?,
+        None => ArchiveFormat::Zip,
+    };
+    Ok(ReleaseOptions {
+        build_dir: PathBuf::from(required("build-dir")?),
+        version: required("version")?,
+        base_url: required("base-url")?,
+        out_dir: PathBuf::from(values.get("out").copied().unwrap_or("release")),
+        name: values.get("name").copied().unwrap_or("game").to_string(),
+        format,
+        manifest: values.get("manifest").map(PathBuf::from),
+        previous: values.get("previous").map(PathBuf::from),
+        signing_key: values.get("signing-key").map(PathBuf::from),
+    })
+}
+
+/// Entry point of the `release` binary
+pub fn cli_main(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    match build_release(&options) {
+        Ok(summary) => {
+            println!("{}", serde_json::to_string_pretty(&summary).unwrap_or_default());
+            0
+        }
+        Err(e) => {
+            eprintln!("Release failed: {}", e);
+            1
+        }
+    }
+}
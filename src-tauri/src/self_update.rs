@@ -0,0 +1,181 @@
+use futures_util::StreamExt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::launcher_core::compat;
+use crate::launcher_core::download;
+use crate::launcher_core::manifest::LauncherUpdate;
+use crate::launcher_core::signing::{self, public_key};
+use crate::paths;
+use crate::state::AppState;
+use crate::logger::log_err;
+
+static UPDATING: AtomicBool = AtomicBool::new(false);
+static DISABLED_REPORTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, thiserror::Error)]
+pub enum SelfUpdateError {
+    #[error("No launcher update is available")]
+    NoUpdate,
+    #[error("Launcher update checksum mismatch")]
+    Checksum,
+    #[error("Launcher update signature is invalid")]
+    Signature,
+    #[error("This launcher can't update itself, please download launcher {0} from the website and install it manually")]
+    Disabled(String),
+}
+
+#[derive(Clone, serde::Serialize)]
+struct LauncherUpdateProgress {
+    version: String,
+    progress: f32, // 0.0 to 100.0
+    status: String,
+}
+
+/// Record the version from tauri.conf.json, called once from setup
+pub fn init(version: String) {
+    compat::set_current_version(version);
+    cleanup_previous();
+}
+
+/// The binary we replaced last time, removed on the next start
+fn previous_exe_path(exe: &Path) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".old");
+    exe.with_file_name(name)
+}
+
+fn cleanup_previous() {
+    if let Ok(exe) = std::env::current_exe() {
+        let old = previous_exe_path(&exe);
+        if old.exists() {
+            log_err!(fs::remove_file(&old), "remove previous launcher");
+        }
+    }
+}
+
+/// Builds without a public key (`signing::public_key`) can't check an update, so they don't self-update
+fn verify_signature(path: &Path, signature: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    signing::verify(&fs::read(path)?, signature)
+}
+
+async fn download(update: &LauncherUpdate, target: &Path, app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let res = reqwest::get(&update.url).await?.error_for_status()?;
+    let total = res.content_length().unwrap_or(0);
+    let mut file = File::create(target)?;
+    let mut downloaded: u64 = 0;
+    let mut stream = res.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+        if total > 0 {
+            app.emit("launcher-update-progress", LauncherUpdateProgress {
+                version: update.version.clone(),
+                progress: downloaded as f32 / total as f32 * 100.0,
+                status: "Downloading".to_string(),
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Put `new` where the running binary is. The running one is moved aside since
+/// Windows won't let us overwrite it but does allow renaming.
+fn replace_exe(new: &Path) -> std::io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let old = previous_exe_path(&exe);
+    if old.exists() {
+        fs::remove_file(&old)?;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(new, fs::Permissions::from_mode(0o755))?;
+    }
+
+    fs::rename(&exe, &old)?;
+    if let Err(e) = fs::rename(new, &exe).or_else(|_| fs::copy(new, &exe).map(|_| ())) {
+        // Put the working launcher back
+        if let Err(restore) = fs::rename(&old, &exe) {
+            log::error!("Failed to restore {} from {}: {}", exe.display(), old.display(), restore);
+            return Err(std::io::Error::new(restore.kind(), format!(
+                "{}. The previous launcher couldn't be put back either, rename {} to {} or reinstall",
+                e, old.display(), exe.display()
+            )));
+        }
+        return Err(e);
+    }
+    log_err!(fs::remove_file(new), "remove {}", new.display());
+    Ok(())
+}
+
+/// Download, verify and install the launcher update, then restart into it
+pub async fn run(app: &AppHandle, update: &LauncherUpdate) -> Result<(), Box<dyn std::error::Error>> {
+    let cache_dir = paths::get_cache_dir();
+    fs::create_dir_all(&cache_dir)?;
+    let target = cache_dir.join(format!("launcher-{}.tmp", update.version));
+
+    log::info!("Updating launcher {} -> {} from {}", compat::current_version(), update.version, update.url);
+    download(update, &target, app).await?;
+
+    app.emit("launcher-update-progress", LauncherUpdateProgress {
+        version: update.version.clone(),
+        progress: 100.0,
+        status: "Verifying".to_string(),
+    })?;
+    if !download::verify_hash(&target, &update.checksum)? {
+        log_err!(fs::remove_file(&target), "remove rejected launcher update");
+        return Err(Box::new(SelfUpdateError::Checksum));
+    }
+    if !verify_signature(&target, &update.signature)? {
+        log_err!(fs::remove_file(&target), "remove rejected launcher update");
+        return Err(Box::new(SelfUpdateError::Signature));
+    }
+
+    replace_exe(&target)?;
+    log::info!("Launcher updated to {}, restarting", update.version);
+    app.restart();
+}
+
+/// Start the self-update in the background, unless one is already running
+pub fn spawn(app: AppHandle, update: Option<LauncherUpdate>) {
+    let Some(update) = update else {
+        log::error!("Launcher is outdated but the manifest has no launcher_update");
+        log_err!(app.emit("launcher-update-error", SelfUpdateError::NoUpdate.to_string()), "emit launcher-update-error");
+        return;
+    };
+    if public_key().is_none() {
+        // Once per run, maybe_run comes back to this every poll
+        if !DISABLED_REPORTED.swap(true, Ordering::SeqCst) {
+            log::warn!("Launcher {} is required but this build has no update key", update.version);
+            let err = SelfUpdateError::Disabled(update.version);
+            log_err!(app.emit("launcher-update-error", err.to_string()), "emit launcher-update-error");
+        }
+        return;
+    }
+    if UPDATING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run(&app, &update).await {
+            log::error!("Launcher update to {} failed: {}", update.version, e);
+            log_err!(app.emit("launcher-update-error", e.to_string()), "emit launcher-update-error");
+        }
+        UPDATING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Self-update if the current manifest requires a newer launcher
+pub fn maybe_run(app: &AppHandle) {
+    let manifest = { app.state::<AppState>().manifest.lock().unwrap().clone() };
+    if let Some(m) = manifest {
+        if compat::is_outdated(&m) {
+            spawn(app.clone(), m.launcher_update);
+        }
+    }
+}
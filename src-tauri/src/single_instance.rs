@@ -0,0 +1,153 @@
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use crate::paths;
+
+const LOCK_FILE: &str = "launcher.lock";
+/// "<port> <token>" of the running instance's listener
+const PORT_FILE: &str = "launcher.port";
+/// How long a second launch keeps trying to reach the first, which may still be starting up
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_MESSAGE: u64 = 64 * 1024;
+/// Rounds of forward-then-acquire before giving up on a launcher that holds the lock but won't answer
+const TAKEOVER_ATTEMPTS: u32 = 3;
+
+/// Held by the first launcher for as long as it runs. The OS releases the lock if it crashes.
+pub struct InstanceLock {
+    _file: File,
+    listener: TcpListener,
+    token: String,
+}
+
+pub enum Instance {
+    Primary(InstanceLock),
+    Secondary, // Another launcher holds the lock, forward our args to it and exit
+}
+
+/// One line of JSON sent from a second launch to the running one
+#[derive(Serialize, Deserialize)]
+struct Forwarded {
+    token: String, // From the port file, so only processes that can read our data dir get through
+    args: Vec<String>,
+}
+
+fn lock_dir() -> PathBuf {
+    paths::get_data_dir()
+}
+
+/// 128 random bits from the std hasher's OS-seeded keys, enough to tell our own launches apart
+fn random_token() -> String {
+    let half = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", half(), half())
+}
+
+/// Take the instance lock, or find out that another launcher has it
+pub fn acquire() -> io::Result<Instance> {
+    let dir = lock_dir();
+    fs::create_dir_all(&dir)?;
+    let file = OpenOptions::new().create(true).write(true).truncate(false).open(dir.join(LOCK_FILE))?;
+    if let Err(e) = file.try_lock_exclusive() {
+        if e.kind() == fs2::lock_contended_error().kind() {
+            return Ok(Instance::Secondary);
+        }
+        return Err(e);
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let token = random_token();
+    let port_path = dir.join(PORT_FILE);
+    fs::write(&port_path, format!("{} {}", listener.local_addr()?.port(), token))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&port_path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(Instance::Primary(InstanceLock { _file: file, listener, token }))
+}
+
+impl InstanceLock {
+    /// Hand args from later launches to `on_args` on a background thread. Keeps the lock until exit.
+    pub fn listen<F: Fn(Vec<String>) + Send + 'static>(self, on_args: F) {
+        std::thread::spawn(move || {
+            for stream in self.listener.incoming().flatten() {
+                match read_forwarded(stream, &self.token) {
+                    Ok(args) => on_args(args),
+                    Err(e) => log::warn!("Ignoring message from another launch: {}", e),
+                }
+            }
+        });
+    }
+}
+
+fn read_forwarded(stream: TcpStream, token: &str) -> io::Result<Vec<String>> {
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(stream.try_clone()?.take(MAX_MESSAGE)).read_line(&mut line)?;
+    let msg: Forwarded = serde_json::from_str(&line)?;
+    if msg.token != token {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "wrong token"));
+    }
+    (&stream).write_all(b"ok\n")?;
+    Ok(msg.args)
+}
+
+fn try_forward(args: &[String]) -> io::Result<()> {
+    let content = fs::read_to_string(lock_dir().join(PORT_FILE))?;
+    let bad_port_file = || io::Error::new(io::ErrorKind::InvalidData, "malformed port file");
+    let (port, token) = content.trim().split_once(' ').ok_or_else(bad_port_file)?;
+    let port: u16 = port.parse().map_err(|_| bad_port_file())?;
+
+    let mut stream = TcpStream::connect_timeout(&SocketAddr::from((Ipv4Addr::LOCALHOST, port)), FORWARD_TIMEOUT)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    let msg = serde_json::to_string(&Forwarded { token: token.to_string(), args: args.to_vec() })?;
+    stream.write_all(msg.as_bytes())?;
+    stream.write_all(b"\n")?;
+
+    // Wait for the ack so we don't exit before the message was read
+    let mut ack = String::new();
+    BufReader::new(stream).read_line(&mut ack)?;
+    if ack.trim() != "ok" {
+        return Err(io::Error::new(io::ErrorKind::ConnectionAborted, "running launcher didn't acknowledge"));
+    }
+    Ok(())
+}
+
+/// Send our command line to the launcher that holds the lock
+pub fn forward(args: &[String]) -> io::Result<()> {
+    let deadline = Instant::now() + FORWARD_TIMEOUT;
+    loop {
+        match try_forward(args) {
+            Ok(()) => return Ok(()),
+            // The port file may be stale or not written yet while the other launcher starts
+            Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hand our args to the launcher holding the lock. If it doesn't answer it may be on its way out
+/// (e.g. `app.restart()` after a self-update) and releasing the lock, so try to take it in between.
+/// Returns the lock if we ended up being the primary after all.
+pub fn forward_or_take_over(args: &[String]) -> io::Result<Option<InstanceLock>> {
+    let mut attempt = 1;
+    loop {
+        let err = match forward(args) {
+            Ok(()) => return Ok(None),
+            Err(e) => e,
+        };
+        if let Instance::Primary(lock) = acquire()? {
+            return Ok(Some(lock));
+        }
+        if attempt >= TAKEOVER_ATTEMPTS {
+            return Err(err);
+        }
+        log::warn!("Running launcher didn't answer ({}), trying again", err);
+        attempt += 1;
+    }
+}
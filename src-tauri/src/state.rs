@@ -1,7 +1,13 @@
+use std::collections::HashSet;
 use std::sync::Mutex;
 use serde::{Deserialize, Serialize};
+use crate::auto_update::AutoUpdateState;
 use crate::config::LauncherConfig;
-use crate::manifest::GameManifest;
+use crate::connectivity::ConnectivityCache;
+use crate::downloader::Target;
+use crate::launcher_core::manifest::GameManifest;
+use crate::poller::PollerControl;
+use crate::supervisor::RunningGame;
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub enum GameStatus {
@@ -12,12 +18,32 @@ pub enum GameStatus {
     Checking,
     Offline,
     Missing,
+    Maintenance, // server_status is "maintenance" or "closed"
+    Running,
+    LauncherOutdated, // Manifest requires a newer launcher, self-update in progress
+}
+
+impl GameStatus {
+    /// Worked out from the manifest and installed version (`poller::compute_status`), as opposed to
+    /// something in progress. Only these get replaced by Offline or a recompute. LauncherOutdated
+    /// stays until the self-update restarts us, the old launcher can't do anything else anyway.
+    pub fn is_derived(&self) -> bool {
+        !matches!(
+            self,
+            GameStatus::Downloading(_) | GameStatus::Updating | GameStatus::Offline | GameStatus::Running | GameStatus::LauncherOutdated
+        )
+    }
 }
 
 pub struct AppState {
     pub config: Mutex<LauncherConfig>,
     pub manifest: Mutex<Option<GameManifest>>,
     pub status: Mutex<GameStatus>,
+    pub connectivity: Mutex<ConnectivityCache>,
+    pub poller: PollerControl,
+    pub auto_update: Mutex<AutoUpdateState>,
+    pub game: Mutex<Option<RunningGame>>,
+    pub download_busy: Mutex<HashSet<Target>>, // See downloader::try_begin
 }
 
 impl AppState {
@@ -26,6 +52,11 @@ impl AppState {
             config: Mutex::new(LauncherConfig::default()),
             manifest: Mutex::new(None),
             status: Mutex::new(GameStatus::Checking),
+            connectivity: Mutex::new(ConnectivityCache::new()),
+            poller: PollerControl::new(),
+            auto_update: Mutex::new(AutoUpdateState::default()),
+            game: Mutex::new(None),
+            download_busy: Mutex::new(HashSet::new()),
         }
     }
 }
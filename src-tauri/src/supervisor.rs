@@ -0,0 +1,154 @@
+use chrono::Local;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::config::CloseBehavior;
+use crate::crash;
+use crate::game_logs;
+use crate::launcher_core::game::{self, RunningInstance};
+use crate::paths;
+use crate::playtime;
+use crate::poller;
+use crate::state::{AppState, GameStatus};
+use crate::logger::log_err;
+
+/// How often a running game's log is checked against its size cap
+const LOG_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The game process we launched and are waiting on
+#[derive(Clone, Serialize, Debug)]
+pub struct RunningGame {
+    pub pid: u32,
+    pub version: String,
+    pub started_at: String, // RFC 3339, local time
+    pub log_file: Option<String>,
+    #[serde(skip)]
+    started: Instant,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct GameExit {
+    pub pid: u32,
+    pub version: String,
+    pub started_at: String,
+    pub ended_at: String,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>, // Unix only, set when the process was killed
+    pub duration_secs: u64,
+    pub log_file: Option<String>,
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+pub fn current(app: &AppHandle) -> Option<RunningGame> {
+    app.state::<AppState>().game.lock().unwrap().clone()
+}
+
+/// The child we're supervising, or failing that anything running out of game/
+pub fn running_instance(app: &AppHandle) -> Option<RunningInstance> {
+    if let Some(running) = current(app) {
+        return Some(RunningInstance {
+            pid: Some(running.pid),
+            exe: paths::get_game_dir(),
+        });
+    }
+    game::find_running_instance()
+}
+
+/// Take ownership of a freshly spawned game process and watch it until it exits.
+/// `log_file` is where its output goes, if it was started with one.
+pub fn supervise(app: &AppHandle, mut child: Child, version: String, log_file: Option<PathBuf>) {
+    let running = RunningGame {
+        pid: child.id(),
+        version,
+        started_at: Local::now().to_rfc3339(),
+        log_file: log_file.as_ref().map(|p| p.to_string_lossy().to_string()),
+        started: Instant::now(),
+    };
+
+    log::info!("Game started (pid {}, version {})", running.pid, running.version);
+    let state = app.state::<AppState>();
+    *state.game.lock().unwrap() = Some(running.clone());
+    *state.status.lock().unwrap() = GameStatus::Running;
+    log_err!(app.emit("status-changed", GameStatus::Running), "emit status-changed");
+    log_err!(app.emit("game-started", running.clone()), "emit game-started");
+
+    if let Some(path) = log_file {
+        let app = app.clone();
+        let pid = running.pid;
+        std::thread::spawn(move || loop {
+            std::thread::sleep(LOG_CHECK_INTERVAL);
+            if current(&app).map(|g| g.pid) != Some(pid) {
+                break;
+            }
+            match game_logs::rotate_if_full(&path) {
+                Ok(true) => log::info!("Rotated game log {}", path.display()),
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to rotate game log {}: {}", path.display(), e),
+            }
+        });
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let status = child.wait();
+        let exit = GameExit {
+            pid: running.pid,
+            version: running.version.clone(),
+            started_at: running.started_at.clone(),
+            ended_at: Local::now().to_rfc3339(),
+            exit_code: status.as_ref().ok().and_then(|s| s.code()),
+            signal: status.as_ref().ok().and_then(exit_signal),
+            duration_secs: running.started.elapsed().as_secs(),
+            log_file: running.log_file.clone(),
+        };
+        on_exit(&app, exit);
+    });
+}
+
+fn on_exit(app: &AppHandle, exit: GameExit) {
+    let state = app.state::<AppState>();
+    *state.game.lock().unwrap() = None;
+    {
+        let mut status = state.status.lock().unwrap();
+        if *status == GameStatus::Running {
+            *status = GameStatus::Checking;
+        }
+    }
+    poller::refresh_status(app);
+    log::info!("Game exited (pid {}, code {:?}, signal {:?}, {}s)", exit.pid, exit.exit_code, exit.signal, exit.duration_secs);
+    log_err!(playtime::record_session(&exit), "record play session");
+
+    if crash::is_crash(&exit) {
+        let config = { state.config.lock().unwrap().clone() };
+        let manifest_version = { state.manifest.lock().unwrap().as_ref().map(|m| m.latest_version.clone()) };
+        match crash::build_bundle(&exit, &config, manifest_version) {
+            Ok(report) => {
+                log::warn!("Game crashed, report saved to {}", report.path);
+                log_err!(app.emit("game-crashed", report), "emit game-crashed");
+            }
+            Err(e) => log::error!("Failed to build crash report: {}", e),
+        }
+    }
+    log_err!(app.emit("game-exited", exit), "emit game-exited");
+
+    // We hid the launcher when the game started, bring it back
+    let close_behavior = { state.config.lock().unwrap().close_behavior.clone() };
+    if let CloseBehavior::MinimizeToTray = close_behavior {
+        if let Some(window) = app.get_webview_window("main") {
+            log_err!(window.show(), "window show");
+            log_err!(window.set_focus(), "window set_focus");
+        }
+    }
+}
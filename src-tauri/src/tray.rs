@@ -4,6 +4,16 @@ use tauri::{
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager, Runtime,
 };
+use crate::logger::log_err;
+
+/// Bring the main window up front, e.g. from the tray or when the launcher is started again
+pub fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        log_err!(window.unminimize(), "window unminimize");
+        log_err!(window.show(), "window show");
+        log_err!(window.set_focus(), "window set_focus");
+    }
+}
 
 pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
     // Main menu items
@@ -35,22 +45,21 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
                 "toggle" => {
                     if let Some(window) = app.get_webview_window("main") {
                         if window.is_visible().unwrap_or(false) {
-                            let _ = window.hide();
+                            log_err!(window.hide(), "window hide");
                         } else {
-                            let _ = window.show();
-                            let _ = window.set_focus();
+                            show_main_window(app);
                         }
                     }
                 }
                 "reload" => {
                     if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.eval("location.reload()");
+                        log_err!(window.eval("location.reload()"), "reload window");
                     }
                 }
                 "lang_vi" | "lang_en" | "lang_jp" | "lang_zh" => {
                     let lang = event.id.as_ref().replace("lang_", "");
                     // Emit to frontend to change language
-                    let _ = app.emit("change-language", lang);
+                    log_err!(app.emit("change-language", lang), "emit change-language");
                 }
                 "quit" => {
                     app.exit(0);
@@ -64,10 +73,9 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
                     let app = tray.app_handle();
                     if let Some(window) = app.get_webview_window("main") {
                         if window.is_visible().unwrap_or(false) {
-                            let _ = window.hide();
+                            log_err!(window.hide(), "window hide");
                         } else {
-                            let _ = window.show();
-                            let _ = window.set_focus();
+                            show_main_window(app);
                         }
                     }
                 }
@@ -0,0 +1,264 @@
+use futures_util::future::join_all;
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+use crate::launcher_core::download;
+use crate::launcher_core::manifest::{self, GameManifest, LauncherUpdate, ManifestLaunch, NewsItem, PreloadInfo, CURRENT_SCHEMA_VERSION};
+use crate::logger::log_err;
+
+const HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+const SERVER_STATUSES: &[&str] = &["online", "maintenance", "closed"];
+
+#[derive(Clone, Copy, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,   // Players would be unable to install or play
+    Warning, // Cosmetic, or something the launcher will silently ignore
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct UrlCheck {
+    pub field: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct ArchiveCheck {
+    pub url: String,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ValidationReport {
+    pub ok: bool, // No errors, warnings are allowed
+    pub schema_version: Option<u32>,
+    pub latest_version: Option<String>,
+    pub issues: Vec<Issue>,
+    pub urls: Vec<UrlCheck>,
+    pub archive: Option<ArchiveCheck>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ValidateOptions {
+    pub check_urls: bool,
+    pub hash_archive: bool, // Download the whole game_zip and compare its checksum
+}
+
+impl ValidationReport {
+    fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.push(Severity::Error, field, message.into());
+    }
+
+    fn warn(&mut self, field: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, field, message.into());
+    }
+
+    fn push(&mut self, severity: Severity, field: &str, message: String) {
+        self.issues.push(Issue { severity, field: field.to_string(), message });
+    }
+}
+
+fn check_semver(report: &mut ValidationReport, field: &str, version: &str) {
+    if let Err(e) = semver::Version::parse(version) {
+        report.error(field, format!("'{}' is not a semantic version: {}", version, e));
+    }
+}
+
+fn check_checksum(report: &mut ValidationReport, field: &str, checksum: &str) {
+    let hash = download::normalize_checksum(checksum);
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        report.error(field, format!("'{}' is not a SHA-256 checksum (64 hex characters, optional 'sha256:' prefix)", checksum));
+    }
+}
+
+/// The launcher parses optional sections leniently, so a typo there is silently dropped.
+/// Parse them strictly here so ops hears about it.
+fn check_section<T: DeserializeOwned>(report: &mut ValidationReport, raw: &Value, field: &str) {
+    if let Some(value) = raw.get(field).filter(|v| !v.is_null()) {
+        if let Err(e) = serde_json::from_value::<T>(value.clone()) {
+            report.warn(field, format!("Malformed, the launcher will ignore it: {}", e));
+        }
+    }
+}
+
+/// Every URL a player's launcher will fetch, with the field it came from
+fn collect_urls(m: &GameManifest) -> Vec<(String, String)> {
+    let mut urls = vec![("game_zip".to_string(), m.game_zip.clone())];
+    if let Some(file_list) = &m.file_list {
+        urls.push(("file_list".to_string(), file_list.clone()));
+    }
+    for (i, bg) in m.presentation.backgrounds.iter().enumerate() {
+        urls.push((format!("backgrounds[{}]", i), bg.clone()));
+    }
+    for (i, item) in m.presentation.news.iter().enumerate() {
+        urls.push((format!("news[{}].image", i), item.image.clone()));
+    }
+    if let Some(preload) = &m.preload {
+        urls.push(("preload.archive".to_string(), preload.archive.clone()));
+    }
+    if let Some(update) = &m.launcher_update {
+        urls.push(("launcher_update.url".to_string(), update.url.clone()));
+    }
+    urls
+}
+
+async fn head(client: &reqwest::Client, field: String, url: String) -> UrlCheck {
+    match client.head(&url).send().await {
+        Ok(resp) => UrlCheck {
+            ok: resp.status().is_success(),
+            status: Some(resp.status().as_u16()),
+            error: None,
+            field,
+            url,
+        },
+        Err(e) => UrlCheck { field, url, status: None, ok: false, error: Some(e.to_string()) },
+    }
+}
+
+/// Download `url` to a temp file and hash it the same way the downloader does
+async fn hash_archive(url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("manifest-validate-{}.zip", std::process::id()));
+    let result = async {
+        let resp = reqwest::get(url).await?.error_for_status()?;
+        let mut file = std::fs::File::create(&path)?;
+        let mut stream = resp.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            file.write_all(&chunk?)?;
+        }
+        drop(file);
+        Ok::<_, Box<dyn std::error::Error>>(download::sha256_file(&path)?)
+    }.await;
+    // Not there if the request failed before anything was written
+    if path.exists() {
+        log_err!(std::fs::remove_file(&path), "remove {}", path.display());
+    }
+    result
+}
+
+/// Check a manifest before it's uploaded: schema, versions, checksum format,
+/// reachability of every URL and optionally the archive checksum itself.
+pub async fn validate_manifest(body: &[u8], options: &ValidateOptions) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let raw: Value = match serde_json::from_slice(body) {
+        Ok(v) => v,
+        Err(e) => {
+            report.error("", format!("Not valid JSON: {}", e));
+            return report;
+        }
+    };
+
+    let schema_version = raw.get("schema_version").and_then(|v| v.as_u64()).map(|v| v as u32);
+    match schema_version {
+        None => report.warn("schema_version", format!("Missing, treated as schema 1 (current is {})", CURRENT_SCHEMA_VERSION)),
+        Some(v) if v > CURRENT_SCHEMA_VERSION => {
+            report.warn("schema_version", format!("{} is newer than this tool understands ({})", v, CURRENT_SCHEMA_VERSION))
+        }
+        _ => {}
+    }
+
+    check_section::<Vec<String>>(&mut report, &raw, "backgrounds");
+    check_section::<Vec<NewsItem>>(&mut report, &raw, "news");
+    check_section::<Vec<String>>(&mut report, &raw, "languages");
+    check_section::<HashMap<String, String>>(&mut report, &raw, "sidebar_links");
+    check_section::<PreloadInfo>(&mut report, &raw, "preload");
+    check_section::<ManifestLaunch>(&mut report, &raw, "launch");
+    check_section::<LauncherUpdate>(&mut report, &raw, "launcher_update");
+
+    let m = match manifest::parse_manifest(body) {
+        Ok(m) => m,
+        Err(e) => {
+            report.error("", format!("Launcher can't parse this manifest: {}", e));
+            return report;
+        }
+    };
+    report.schema_version = Some(m.schema_version);
+    report.latest_version = Some(m.latest_version.clone());
+
+    check_semver(&mut report, "latest_version", &m.latest_version);
+    check_checksum(&mut report, "checksum", &m.checksum);
+    if let Some(status) = &m.server_status {
+        if !SERVER_STATUSES.contains(&status.as_str()) {
+            report.warn("server_status", format!("Unknown status '{}', expected one of {:?}", status, SERVER_STATUSES));
+        }
+    }
+    if let Some(required) = &m.min_launcher_version {
+        check_semver(&mut report, "min_launcher_version", required);
+        if m.launcher_update.is_none() {
+            report.warn("launcher_update", "min_launcher_version is set but outdated launchers have nothing to update to");
+        }
+    }
+    if let Some(update) = &m.launcher_update {
+        check_semver(&mut report, "launcher_update.version", &update.version);
+        check_checksum(&mut report, "launcher_update.checksum", &update.checksum);
+    }
+    if let Some(preload) = &m.preload {
+        check_semver(&mut report, "preload.version", &preload.version);
+        check_checksum(&mut report, "preload.checksum", &preload.checksum);
+    }
+    if m.presentation.languages.is_empty() {
+        report.warn("languages", "No languages listed");
+    }
+
+    if options.check_urls {
+        let client = reqwest::Client::builder().timeout(HEAD_TIMEOUT).build().unwrap_or_default();
+        let checks = collect_urls(&m).into_iter().map(|(field, url)| head(&client, field, url));
+        report.urls = join_all(checks).await;
+        let failed: Vec<(String, String)> = report.urls.iter()
+            .filter(|c| !c.ok)
+            .map(|c| (c.field.clone(), c.url.clone()))
+            .collect();
+        for (field, url) in failed {
+            // Only the archives stop players from installing
+            if field == "game_zip" || field.ends_with(".archive") || field.ends_with(".url") {
+                report.error(&field, format!("{} is not reachable", url));
+            } else {
+                report.warn(&field, format!("{} is not reachable", url));
+            }
+        }
+    }
+
+    if options.hash_archive {
+        let expected = download::normalize_checksum(&m.checksum);
+        let check = match hash_archive(&m.game_zip).await {
+            Ok(actual) => ArchiveCheck {
+                ok: actual == expected,
+                actual: Some(actual),
+                error: None,
+                url: m.game_zip.clone(),
+                expected,
+            },
+            Err(e) => ArchiveCheck {
+                url: m.game_zip.clone(),
+                expected,
+                actual: None,
+                ok: false,
+                error: Some(e.to_string()),
+            },
+        };
+        if !check.ok {
+            report.error("checksum", "game_zip does not match the checksum");
+        }
+        report.archive = Some(check);
+    }
+
+    report.ok = !report.issues.iter().any(|i| i.severity == Severity::Error);
+    report
+}
@@ -0,0 +1,55 @@
+//! XDG autostart entry written for `launch_at_startup`.
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri_app_lib::autostart::{exec_quote, reconcile_with, AutostartBackend, XdgAutostart};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("launcher-test-autostart-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn reconcile_writes_and_removes_entry() {
+    let dir = temp_dir("toggle");
+    let backend = XdgAutostart::new(dir.clone());
+    let exe = Path::new("/opt/ganhrong/launcher");
+
+    reconcile_with(&backend, true, exe).unwrap();
+    let entry = fs::read_to_string(backend.entry_path()).unwrap();
+    assert!(entry.starts_with("[Desktop Entry]\n"));
+    assert!(entry.contains("\nExec=/opt/ganhrong/launcher --minimized\n"));
+    assert!(backend.is_enabled(exe));
+
+    // Already there, nothing to do
+    reconcile_with(&backend, true, exe).unwrap();
+
+    reconcile_with(&backend, false, exe).unwrap();
+    assert!(!backend.entry_path().exists());
+    // Disabling twice is fine
+    reconcile_with(&backend, false, exe).unwrap();
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn moved_launcher_rewrites_entry() {
+    let dir = temp_dir("moved");
+    let backend = XdgAutostart::new(dir.clone());
+    reconcile_with(&backend, true, Path::new("/old/launcher")).unwrap();
+
+    let new_exe = Path::new("/home/me/Games/GanhRong Launcher/launcher");
+    assert!(!backend.is_enabled(new_exe));
+    reconcile_with(&backend, true, new_exe).unwrap();
+    assert!(backend.is_enabled(new_exe));
+    let entry = fs::read_to_string(backend.entry_path()).unwrap();
+    assert!(entry.contains("Exec=\"/home/me/Games/GanhRong Launcher/launcher\" --minimized"));
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn exec_arguments_are_escaped() {
+    assert_eq!(exec_quote("/usr/bin/launcher"), "/usr/bin/launcher");
+    assert_eq!(exec_quote("/a b/c"), "\"/a b/c\"");
+    assert_eq!(exec_quote("/a/$HOME\"x"), "\"/a/\\$HOME\\\"x\"");
+    assert_eq!(exec_quote("/a/100%"), "/a/100%%");
+}
@@ -0,0 +1,322 @@
+//! Local mock CDN and per-test install root shared by the integration tests.
+#![allow(dead_code)] // Each test binary uses a different subset
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use sha2::{Digest, Sha256};
+use tauri_app_lib::paths;
+
+/// Behaviour switches, shared by every file the CDN serves
+#[derive(Clone, Debug)]
+pub struct Knobs {
+    pub ranges: bool,                  // Honour Range requests with 206
+    pub etags: bool,                   // Send ETag and answer If-None-Match with 304
+    pub chunk_delay: Option<Duration>, // Sleep between 1 KiB chunks of the body
+    pub truncate_next: Option<usize>,  // Next body response stops after this many bytes, then closes
+    pub fail_next: usize,              // Answer the next N requests with 503
+}
+
+impl Default for Knobs {
+    fn default() -> Self {
+        Self { ranges: true, etags: true, chunk_delay: None, truncate_next: None, fail_next: 0 }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestLog {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>, // Lowercase names
+    pub body: Vec<u8>,                    // Request body, for uploads
+    pub status: u16,
+}
+
+#[derive(Default)]
+struct CdnState {
+    files: HashMap<String, (Vec<u8>, String)>, // path -> (body, content type)
+    knobs: Knobs,
+    requests: Vec<RequestLog>,
+}
+
+/// HTTP/1.1 server on 127.0.0.1 with one connection per request (`Connection: close`),
+/// so truncation is just closing the socket early.
+pub struct MockCdn {
+    pub base_url: String,
+    state: Arc<Mutex<CdnState>>,
+}
+
+impl MockCdn {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock CDN");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(CdnState::default()));
+
+        let shared = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = shared.clone();
+                thread::spawn(move || handle(stream, &state));
+            }
+        });
+        Self { base_url, state }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    pub fn serve(&self, path: &str, body: Vec<u8>, content_type: &str) {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        self.state.lock().unwrap().files.insert(path, (body, content_type.to_string()));
+    }
+
+    pub fn knobs(&self, update: impl FnOnce(&mut Knobs)) {
+        update(&mut self.state.lock().unwrap().knobs);
+    }
+
+    pub fn requests(&self) -> Vec<RequestLog> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn requests_for(&self, path: &str) -> Vec<RequestLog> {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        self.requests().into_iter().filter(|r| r.path == path).collect()
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let len: usize = headers.get("content-length").and_then(|l| l.parse().ok()).unwrap_or(0);
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).ok()?;
+    Some(Request { method, path, headers, body })
+}
+
+fn etag_of(body: &[u8]) -> String {
+    format!("\"{}\"", &hex::encode(Sha256::digest(body))[..16])
+}
+
+/// `bytes=N-` only, which is all the downloader sends
+fn parse_range(header: &str, len: usize) -> Option<usize> {
+    let start: usize = header.strip_prefix("bytes=")?.strip_suffix('-')?.parse().ok()?;
+    (start < len).then_some(start)
+}
+
+fn handle(mut stream: TcpStream, state: &Mutex<CdnState>) {
+    let Some(Request { method, path, headers, body: request_body }) = read_request(&stream) else {
+        return;
+    };
+
+    let mut extra_headers: Vec<(String, String)> = Vec::new();
+    let (status, body, content_type, knobs) = {
+        let mut state = state.lock().unwrap();
+        let knobs = state.knobs.clone();
+        let response = if state.knobs.fail_next > 0 {
+            state.knobs.fail_next -= 1;
+            (503, b"Service Unavailable".to_vec(), "text/plain".to_string())
+        } else {
+            match state.files.get(&path) {
+                None => (404, b"Not Found".to_vec(), "text/plain".to_string()),
+                Some((body, content_type)) => {
+                    let etag = etag_of(body);
+                    if knobs.etags {
+                        extra_headers.push(("ETag".into(), etag.clone()));
+                    }
+                    let range = headers.get("range").filter(|_| knobs.ranges).and_then(|r| parse_range(r, body.len()));
+                    if knobs.etags && headers.get("if-none-match") == Some(&etag) {
+                        (304, Vec::new(), content_type.clone())
+                    } else if headers.contains_key("range") && knobs.ranges && range.is_none() {
+                        (416, Vec::new(), content_type.clone())
+                    } else if let Some(start) = range {
+                        extra_headers.push(("Content-Range".into(), format!("bytes {}-{}/{}", start, body.len() - 1, body.len())));
+                        (206, body[start..].to_vec(), content_type.clone())
+                    } else {
+                        (200, body.clone(), content_type.clone())
+                    }
+                }
+            }
+        };
+        state.requests.push(RequestLog { method: method.clone(), path, headers, body: request_body, status: response.0 });
+        (response.0, response.1, response.2, knobs)
+    };
+
+    // Truncation only applies to responses that carry a body
+    let truncate = if method == "GET" && !body.is_empty() {
+        let mut state = state.lock().unwrap();
+        state.knobs.truncate_next.take()
+    } else {
+        None
+    };
+
+    let reason = match status {
+        200 => "OK",
+        206 => "Partial Content",
+        304 => "Not Modified",
+        404 => "Not Found",
+        416 => "Range Not Satisfiable",
+        _ => "Service Unavailable",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nContent-Type: {}\r\nConnection: close\r\n",
+        status, reason, body.len(), content_type
+    );
+    if knobs.ranges {
+        head.push_str("Accept-Ranges: bytes\r\n");
+    }
+    for (name, value) in extra_headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    if stream.write_all(head.as_bytes()).is_err() || method == "HEAD" {
+        return;
+    }
+
+    let body = match truncate {
+        Some(n) => &body[..n.min(body.len())],
+        None => &body[..],
+    };
+    for chunk in body.chunks(1024) {
+        if stream.write_all(chunk).is_err() {
+            return;
+        }
+        if let Some(delay) = knobs.chunk_delay {
+            let _ = stream.flush();
+            thread::sleep(delay);
+        }
+    }
+    let _ = stream.flush();
+    // Dropping the stream closes the connection, short of Content-Length if truncated
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+    let mut drain = Vec::new();
+    let _ = stream.read_to_end(&mut drain);
+}
+
+static ROOT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Temp directory used as the launcher's app dir (game/, cache/, logs/) for one test.
+/// `paths` is process-global, so tests holding a TestRoot run one at a time.
+pub struct TestRoot {
+    pub dir: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TestRoot {
+    pub fn new(name: &str) -> Self {
+        let guard = ROOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = std::env::temp_dir().join(format!("launcher-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        paths::set_root_override(Some(dir.clone()));
+        Self { dir, _guard: guard }
+    }
+
+    pub fn game_dir(&self) -> PathBuf {
+        self.dir.join("game")
+    }
+
+    pub fn cache_dir(&self) -> PathBuf {
+        self.dir.join("cache")
+    }
+}
+
+impl Drop for TestRoot {
+    fn drop(&mut self) {
+        paths::set_root_override(None);
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Zip archive of (path, contents) pairs, like a release build
+/// Same files, same bytes: entry times are fixed so checksums don't change between calls
+pub fn make_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut buf = std::io::Cursor::new(Vec::new());
+    {
+        let mut zip = zip::ZipWriter::new(&mut buf);
+        let options = zip::write::FileOptions::default().last_modified_time(zip::DateTime::default());
+        for (name, content) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+    buf.into_inner()
+}
+
+/// tar.zst archive of (path, contents) pairs
+pub fn make_tar_zst(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let encoder = zstd::Encoder::new(Vec::new(), 3).unwrap();
+    let mut tar = tar::Builder::new(encoder);
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, name, *content).unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap()
+}
+
+/// Incompressible bytes, so archives are big enough to truncate and resume
+pub fn noise(len: usize) -> Vec<u8> {
+    let mut state: u32 = 0x1234_5678;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+pub fn manifest_json(cdn: &MockCdn, version: &str, archive: &str, checksum: &str) -> Vec<u8> {
+    serde_json::to_vec_pretty(&serde_json::json!({
+        "schema_version": 2,
+        "game_name": "Test Game",
+        "game_exe": "game.exe",
+        "latest_version": version,
+        "game_zip": cdn.url(archive),
+        "checksum": format!("sha256:{}", checksum),
+        "server_status": "online",
+        "backgrounds": [],
+        "news": [],
+        "languages": ["en"],
+    }))
+    .unwrap()
+}
+
+pub fn read(path: &Path) -> String {
+    std::fs::read_to_string(path).unwrap_or_default()
+}
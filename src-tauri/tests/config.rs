@@ -0,0 +1,129 @@
+//! Loading, migrating and saving config.json.
+mod common;
+
+use std::fs;
+use common::TestRoot;
+use tauri_app_lib::config::{AutoUpdateMode, LauncherConfig, UpdateWindow, CURRENT_CONFIG_VERSION};
+use tauri_app_lib::launcher_core::manifest::GameManifest;
+
+fn write(root: &TestRoot, name: &str, content: &str) {
+    fs::write(root.dir.join(name), content).unwrap();
+}
+
+fn manifest_with(languages: &[&str]) -> GameManifest {
+    serde_json::from_value(serde_json::json!({
+        "game_name": "Test",
+        "latest_version": "1.0.0",
+        "game_zip": "http://localhost/game.zip",
+        "checksum": "00",
+        "languages": languages,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn bad_field_only_resets_that_field() {
+    let root = TestRoot::new("config-lenient");
+    write(&root, "config.json", r#"{
+        "version": 2,
+        "language": "vi",
+        "close_behavior": "Exit",
+        "launch_at_startup": "yes please",
+        "update_check_interval_mins": 30,
+        "auto_update": { "mode": "DownloadOnly" },
+        "some_future_field": true
+    }"#);
+
+    let config = LauncherConfig::load();
+    assert_eq!(config.language, "vi");
+    assert_eq!(config.update_check_interval_mins, 30);
+    assert_eq!(config.auto_update.mode, AutoUpdateMode::DownloadOnly);
+    assert!(!config.launch_at_startup);
+}
+
+#[test]
+fn unversioned_config_is_migrated() {
+    let root = TestRoot::new("config-migrate");
+    write(&root, "config.json", r#"{ "language": "ja-JP", "close_behavior": "MinimizeToTray", "launch_at_startup": true }"#);
+
+    let config = LauncherConfig::load();
+    assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+    assert_eq!(config.language, "jp");
+    assert!(config.launch_at_startup);
+}
+
+#[test]
+fn version_zero_is_migrated_like_v1() {
+    let root = TestRoot::new("config-zero");
+    write(&root, "config.json", r#"{ "version": 0, "language": "en-GB" }"#);
+
+    let config = LauncherConfig::load();
+    assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+    assert_eq!(config.language, "en");
+}
+
+#[test]
+fn save_keeps_backup_and_corrupt_file_falls_back_to_it() {
+    let root = TestRoot::new("config-backup");
+    let mut config = LauncherConfig { language: "zh".to_string(), ..Default::default() };
+    config.save().unwrap();
+    config.language = "vi".to_string();
+    config.save().unwrap();
+
+    assert!(!root.dir.join("config.json.tmp").exists());
+    let backup: LauncherConfig = serde_json::from_str(&fs::read_to_string(root.dir.join("config.json.bak")).unwrap()).unwrap();
+    assert_eq!(backup.language, "zh");
+
+    write(&root, "config.json", "{ \"language\": \"vi\", \"close_beh");
+    assert_eq!(LauncherConfig::load().language, "zh");
+}
+
+#[test]
+fn missing_config_loads_defaults() {
+    let _root = TestRoot::new("config-missing");
+    let config = LauncherConfig::load();
+    assert_eq!(config.language, "en");
+    assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+}
+
+#[test]
+fn legacy_config_is_moved_to_config_dir() {
+    let root = TestRoot::new("config-legacy");
+    let old_dir = root.dir.join("old-install");
+    fs::create_dir_all(&old_dir).unwrap();
+    fs::write(old_dir.join("config.json"), r#"{ "language": "vi", "launch_at_startup": true }"#).unwrap();
+
+    let config = LauncherConfig::load_with_legacy(Some(&old_dir));
+    assert_eq!(config.language, "vi");
+    assert!(config.launch_at_startup);
+
+    // Saved to the real location, so the next load doesn't need the old file
+    let saved: LauncherConfig = serde_json::from_str(&fs::read_to_string(root.dir.join("config.json")).unwrap()).unwrap();
+    assert_eq!(saved.language, "vi");
+    assert_eq!(saved.version, CURRENT_CONFIG_VERSION);
+    fs::remove_file(old_dir.join("config.json")).unwrap();
+    assert_eq!(LauncherConfig::load_with_legacy(Some(&old_dir)).language, "vi");
+}
+
+#[test]
+fn validate_fixes_values_against_manifest() {
+    let mut config = LauncherConfig {
+        language: "fr".to_string(),
+        update_check_interval_mins: 0,
+        ..Default::default()
+    };
+    config.auto_update.window = Some(UpdateWindow { start: "25:00".to_string(), end: "06:00".to_string() });
+    config.launch.default_profile = Some("ultra".to_string());
+
+    let fixed = config.validate(Some(&manifest_with(&["vi", "en"])));
+    assert_eq!(fixed.len(), 4);
+    assert_eq!(config.language, "en");
+    assert_eq!(config.update_check_interval_mins, 10);
+    assert!(config.auto_update.window.is_none());
+    assert!(config.launch.default_profile.is_none());
+
+    // Nothing to compare language against without a manifest
+    let mut config = LauncherConfig { language: "fr".to_string(), ..Default::default() };
+    assert!(config.validate(None).is_empty());
+    assert!(config.validate(Some(&manifest_with(&[]))).is_empty());
+}
@@ -0,0 +1,121 @@
+//! Crash detection, report bundles, retention and upload to the mock CDN.
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use common::{MockCdn, TestRoot};
+use tauri_app_lib::config::LauncherConfig;
+use tauri_app_lib::crash;
+use tauri_app_lib::paths;
+use tauri_app_lib::supervisor::GameExit;
+
+fn exit(exit_code: Option<i32>, signal: Option<i32>) -> GameExit {
+    GameExit {
+        pid: 42,
+        version: "1.0.0".to_string(),
+        started_at: "2026-01-02T10:00:00+00:00".to_string(),
+        ended_at: "2026-01-02T10:05:00+00:00".to_string(),
+        exit_code,
+        signal,
+        duration_secs: 300,
+        log_file: None,
+    }
+}
+
+fn zip_entry(path: &str, name: &str) -> Option<String> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path).unwrap()).unwrap();
+    let mut file = archive.by_name(name).ok()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content).unwrap();
+    Some(content)
+}
+
+#[test]
+fn non_zero_exit_or_signal_is_a_crash() {
+    assert!(!crash::is_crash(&exit(Some(0), None)));
+    assert!(crash::is_crash(&exit(Some(1), None)));
+    assert!(crash::is_crash(&exit(Some(-1073741819), None)));
+    assert!(crash::is_crash(&exit(None, Some(11))));
+    // No code and no signal: we couldn't wait on it, nothing to report
+    assert!(!crash::is_crash(&exit(None, None)));
+}
+
+#[test]
+fn bundle_has_logs_info_and_redacted_config() {
+    let _root = TestRoot::new("crash-bundle");
+    let logs = paths::get_logs_dir();
+    fs::create_dir_all(&logs).unwrap();
+    fs::write(logs.join("game-20260102-100000.log"), "Segfault in renderer").unwrap();
+    fs::write(logs.join("launcher.log"), "launcher says hi").unwrap();
+
+    let mut config = LauncherConfig::default();
+    config.launch.extra_args = vec!["-token=hunter2".to_string()];
+    config.launch.env = BTreeMap::from([("API_TOKEN".to_string(), "s3cret".to_string())]);
+    let mut crashed = exit(Some(3), None);
+    crashed.log_file = Some(logs.join("game-20260102-100000.log").to_string_lossy().to_string());
+
+    let report = crash::build_bundle(&crashed, &config, Some("1.1.0".to_string())).unwrap();
+    assert!(report.name.starts_with("crash-") && report.name.ends_with(".zip"));
+
+    let info: serde_json::Value = serde_json::from_str(&zip_entry(&report.path, "crash.json").unwrap()).unwrap();
+    assert_eq!(info["exit"]["exit_code"], 3);
+    assert_eq!(info["manifest_version"], "1.1.0");
+    assert_eq!(zip_entry(&report.path, "game.log").unwrap(), "Segfault in renderer");
+    assert_eq!(zip_entry(&report.path, "launcher.log").unwrap(), "launcher says hi");
+
+    let config_json = zip_entry(&report.path, "config.json").expect("redacted config in the bundle");
+    assert!(config_json.contains("\"language\""));
+    assert!(!config_json.contains("hunter2"));
+    assert!(!config_json.contains("s3cret"));
+}
+
+#[test]
+fn report_path_only_accepts_existing_reports_by_name() {
+    let _root = TestRoot::new("crash-report-path");
+    let report = crash::build_bundle(&exit(Some(1), None), &LauncherConfig::default(), None).unwrap();
+
+    assert_eq!(crash::report_path(&report.name).unwrap().to_string_lossy(), report.path);
+    for name in ["../config.json", "crash-../../secret.zip", "crash-a/b.zip", "crash-a\\b.zip", "other.zip", "crash-x.txt"] {
+        assert!(crash::report_path(name).is_err(), "{} should be rejected", name);
+    }
+    assert!(crash::report_path("crash-19990101-000000.zip").is_err());
+}
+
+#[test]
+fn only_the_newest_reports_are_kept() {
+    let _root = TestRoot::new("crash-retention");
+    let dir = crash::get_crash_reports_dir();
+    fs::create_dir_all(&dir).unwrap();
+    for day in 1..=12 {
+        fs::write(dir.join(format!("crash-202001{:02}-000000.zip", day)), b"old").unwrap();
+    }
+
+    let report = crash::build_bundle(&exit(Some(1), None), &LauncherConfig::default(), None).unwrap();
+    let mut left: Vec<String> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().to_string()).collect();
+    left.sort();
+    assert_eq!(left.len(), 10);
+    assert_eq!(left[0], "crash-20200104-000000.zip");
+    assert_eq!(left.last().unwrap(), &report.name);
+}
+
+#[tokio::test]
+async fn upload_posts_the_zip() {
+    let _root = TestRoot::new("crash-upload");
+    let cdn = MockCdn::start();
+    cdn.serve("crash", b"ok".to_vec(), "text/plain");
+    let report = crash::build_bundle(&exit(Some(1), None), &LauncherConfig::default(), None).unwrap();
+    let path = crash::report_path(&report.name).unwrap();
+
+    crash::upload(&path, &cdn.url("crash")).await.unwrap();
+    let request = cdn.requests_for("crash").pop().unwrap();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.headers["content-type"], "application/zip");
+    assert_eq!(request.headers["x-crash-report"], report.name);
+    assert_eq!(request.body, fs::read(&path).unwrap());
+
+    // Endpoint errors are reported, not swallowed
+    cdn.knobs(|k| k.fail_next = 1);
+    assert!(crash::upload(&path, &cdn.url("crash")).await.is_err());
+    assert!(crash::upload(&path, &cdn.url("missing")).await.is_err());
+}
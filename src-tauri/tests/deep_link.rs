@@ -0,0 +1,84 @@
+//! ganhrong:// links: parsing, allowlist and the launch plan they produce.
+use std::path::Path;
+use tauri_app_lib::config::LaunchSettings;
+use tauri_app_lib::deep_link::{self, DeepLink, DeepLinkError};
+use tauri_app_lib::launcher_core::launch::{self, LaunchError};
+use tauri_app_lib::launcher_core::manifest::GameManifest;
+
+fn manifest() -> GameManifest {
+    serde_json::from_value(serde_json::json!({
+        "game_name": "Test",
+        "latest_version": "1.0.0",
+        "game_zip": "http://localhost/game.zip",
+        "checksum": "00",
+        "news": [{ "id": "patch-1.1", "title": "Patch 1.1", "image": "", "date": "2026.1.1" }],
+        "launch": {
+            "locked_args": ["-server=prod"],
+            "profiles": [{ "name": "Windowed", "args": ["-windowed"] }],
+            "servers": [{ "id": "asia-1", "args": ["-connect=asia1.ganhrong.tech:7777"] }]
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn parses_supported_links() {
+    assert_eq!(deep_link::parse("ganhrong://launch"), Ok(DeepLink::Launch { profile: None }));
+    assert_eq!(
+        deep_link::parse("ganhrong://launch?profile=Safe+mode"),
+        Ok(DeepLink::Launch { profile: Some("Safe mode".to_string()) })
+    );
+    assert_eq!(
+        deep_link::parse("ganhrong://join?server=asia-1&profile=Windowed"),
+        Ok(DeepLink::Join { server: "asia-1".to_string(), profile: Some("Windowed".to_string()) })
+    );
+    assert_eq!(deep_link::parse("ganhrong://news/patch-1.1"), Ok(DeepLink::News { id: "patch-1.1".to_string() }));
+    assert_eq!(deep_link::parse("GANHRONG://LAUNCH/"), Ok(DeepLink::Launch { profile: None }));
+}
+
+#[test]
+fn rejects_malformed_and_unsafe_links() {
+    assert_eq!(deep_link::parse("https://ganhrong.tech/launch"), Err(DeepLinkError::WrongScheme));
+    assert_eq!(deep_link::parse("ganhrong://uninstall"), Err(DeepLinkError::UnknownAction("uninstall".to_string())));
+    assert_eq!(deep_link::parse("ganhrong://join"), Err(DeepLinkError::Missing("server")));
+    assert_eq!(deep_link::parse("ganhrong://news"), Err(DeepLinkError::Missing("news id")));
+    assert_eq!(deep_link::parse("ganhrong://launch?args=-dev"), Err(DeepLinkError::UnexpectedParam("args".to_string())));
+    assert_eq!(deep_link::parse("ganhrong://launch?profile=-console"), Err(DeepLinkError::InvalidValue("profile")));
+    assert_eq!(deep_link::parse("ganhrong://join?server=a%20b;rm"), Err(DeepLinkError::InvalidValue("server")));
+    assert_eq!(deep_link::parse("ganhrong://join?server=a&server=b"), Err(DeepLinkError::InvalidValue("server")));
+    assert_eq!(deep_link::parse("ganhrong://news/..%2F..%2Fetc"), Err(DeepLinkError::InvalidValue("news id")));
+    assert_eq!(deep_link::parse("ganhrong://launch/extra"), Err(DeepLinkError::Malformed));
+    assert_eq!(deep_link::parse("ganhrong://user@launch"), Err(DeepLinkError::Malformed));
+    assert_eq!(deep_link::parse(&format!("ganhrong://launch?profile={}", "a".repeat(3000))), Err(DeepLinkError::Malformed));
+}
+
+#[test]
+fn validate_only_allows_what_the_manifest_offers() {
+    let m = manifest();
+    let settings = LaunchSettings::default();
+    let check = |link: &str| deep_link::parse(link).unwrap().validate(&m, &settings);
+
+    assert!(check("ganhrong://launch?profile=Windowed").is_ok());
+    assert!(check("ganhrong://join?server=asia-1").is_ok());
+    assert!(check("ganhrong://news/patch-1.1").is_ok());
+    assert_eq!(check("ganhrong://launch?profile=Dev"), Err(DeepLinkError::NotAllowed("Profile", "Dev".to_string())));
+    assert_eq!(check("ganhrong://join?server=eu-1"), Err(DeepLinkError::NotAllowed("Server", "eu-1".to_string())));
+    assert_eq!(check("ganhrong://news/other"), Err(DeepLinkError::NotAllowed("News", "other".to_string())));
+}
+
+#[test]
+fn join_appends_server_args_from_manifest() {
+    let m = manifest();
+    let plan = launch::build_plan(m.launch.as_ref(), &LaunchSettings::default(), Some("Windowed"), Some("asia-1"), Path::new("/game")).unwrap();
+    assert_eq!(plan.args, vec!["-server=prod", "-windowed", "-connect=asia1.ganhrong.tech:7777"]);
+
+    let err = launch::build_plan(m.launch.as_ref(), &LaunchSettings::default(), None, Some("eu-1"), Path::new("/game")).unwrap_err();
+    assert!(matches!(err, LaunchError::UnknownServer(s) if s == "eu-1"));
+}
+
+#[test]
+fn finds_link_in_command_line() {
+    let args: Vec<String> = ["launcher", "--minimized", "ganhrong://launch"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(deep_link::find_in_args(&args), Some("ganhrong://launch"));
+    assert_eq!(deep_link::find_in_args(&args[..2]), None);
+}
@@ -0,0 +1,246 @@
+//! Download, resume, verify, extract and swap into game/ against the mock CDN.
+mod common;
+
+use std::time::Duration;
+use common::{make_tar_zst, make_zip, noise, read, sha256_hex, MockCdn, TestRoot};
+use tauri_app_lib::launcher_core::download::{self, FileProblem};
+use tauri_app_lib::launcher_core::game;
+use tauri_app_lib::launcher_core::manifest::{FileHash, FileList};
+use tauri_app_lib::launcher_core::progress::{NoProgress, RecordingProgress};
+
+fn game_files(version: &str) -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("game.exe", format!("exe {}", version).into_bytes()),
+        ("data/level1.dat", noise(64 * 1024)),
+    ]
+}
+
+fn zip_of(files: &[(&'static str, Vec<u8>)]) -> Vec<u8> {
+    let refs: Vec<(&str, &[u8])> = files.iter().map(|(n, c)| (*n, c.as_slice())).collect();
+    make_zip(&refs)
+}
+
+#[tokio::test]
+async fn installs_zip_end_to_end() {
+    let root = TestRoot::new("install-zip");
+    let cdn = MockCdn::start();
+    let archive = zip_of(&game_files("1.0.0"));
+    let checksum = format!("sha256:{}", sha256_hex(&archive).to_uppercase());
+    cdn.serve("game-1.0.0.zip", archive, "application/zip");
+
+    let sink = RecordingProgress::default();
+    download::download_and_install_game(&cdn.url("game-1.0.0.zip"), &checksum, "1.0.0", &sink).await.unwrap();
+
+    assert_eq!(game::get_local_version(), "1.0.0");
+    assert_eq!(read(&root.game_dir().join("game.exe")), "exe 1.0.0");
+    assert_eq!(std::fs::read(root.game_dir().join("data/level1.dat")).unwrap(), noise(64 * 1024));
+    assert_eq!(sink.phases(), vec!["Downloading", "Verifying", "Installing"]);
+    // Archive, marker and temp dirs are cleaned up
+    assert!(!download::staged_archive_path("1.0.0").exists());
+    assert!(!root.dir.join("game.extracting").exists());
+}
+
+#[tokio::test]
+async fn installs_tar_zst() {
+    let root = TestRoot::new("install-tar-zst");
+    let cdn = MockCdn::start();
+    let files = game_files("2.0.0");
+    let refs: Vec<(&str, &[u8])> = files.iter().map(|(n, c)| (*n, c.as_slice())).collect();
+    let archive = make_tar_zst(&refs);
+    let checksum = sha256_hex(&archive);
+    cdn.serve("game-2.0.0.tar.zst", archive, "application/zstd");
+
+    download::download_and_install_game(&cdn.url("game-2.0.0.tar.zst"), &checksum, "2.0.0", &NoProgress).await.unwrap();
+
+    assert_eq!(game::get_local_version(), "2.0.0");
+    assert_eq!(read(&root.game_dir().join("game.exe")), "exe 2.0.0");
+}
+
+#[tokio::test]
+async fn wrong_checksum_leaves_existing_install_alone() {
+    let root = TestRoot::new("wrong-checksum");
+    let cdn = MockCdn::start();
+    let old = zip_of(&game_files("1.0.0"));
+    cdn.serve("game-1.0.0.zip", old.clone(), "application/zip");
+    download::download_and_install_game(&cdn.url("game-1.0.0.zip"), &sha256_hex(&old), "1.0.0", &NoProgress).await.unwrap();
+
+    cdn.serve("game-1.1.0.zip", zip_of(&game_files("1.1.0")), "application/zip");
+    let result = download::download_and_install_game(&cdn.url("game-1.1.0.zip"), &sha256_hex(b"something else"), "1.1.0", &NoProgress).await;
+
+    assert!(result.is_err());
+    assert_eq!(game::get_local_version(), "1.0.0");
+    assert_eq!(read(&root.game_dir().join("game.exe")), "exe 1.0.0");
+    assert!(!root.cache_dir().join("game.tmp-1.1.0.zip").exists());
+    assert!(!download::is_staged("1.1.0", &sha256_hex(b"something else")));
+}
+
+#[tokio::test]
+async fn truncated_download_resumes_with_range() {
+    let root = TestRoot::new("resume");
+    let cdn = MockCdn::start();
+    let archive = make_zip(&[("game.exe", &noise(64 * 1024))]);
+    let checksum = sha256_hex(&archive);
+    let cut = archive.len() * 3 / 4;
+    cdn.serve("game.zip", archive.clone(), "application/zip");
+    cdn.knobs(|k| k.truncate_next = Some(cut));
+
+    // Connection drops partway, the partial file stays for the next attempt
+    let first = download::download_archive(&cdn.url("game.zip"), &checksum, "1.0.0", &NoProgress).await;
+    assert!(first.is_err());
+    let partial = root.cache_dir().join("game.tmp-1.0.0.zip");
+    assert_eq!(std::fs::metadata(&partial).unwrap().len(), cut as u64);
+
+    // ~16 KiB left at 1 KiB per 50ms, so a speed sample gets taken
+    cdn.knobs(|k| k.chunk_delay = Some(Duration::from_millis(50)));
+    let sink = RecordingProgress::default();
+    let staged = download::download_archive(&cdn.url("game.zip"), &checksum, "1.0.0", &sink).await.unwrap();
+    assert_eq!(std::fs::read(&staged).unwrap(), archive);
+    assert!(download::is_staged("1.0.0", &checksum));
+
+    let resumed = cdn.requests_for("game.zip").pop().unwrap();
+    assert_eq!(resumed.headers.get("range").map(String::as_str), Some(format!("bytes={}-", cut).as_str()));
+    assert_eq!(resumed.status, 206);
+    // Progress picks up from the bytes already on disk, speed only counts the new ones
+    let events = sink.events();
+    assert!(events[0].downloaded > cut as u64);
+    assert_eq!(events[0].total, archive.len() as u64);
+    assert!(events.iter().any(|e| e.speed > 0.0));
+    assert!(events.iter().all(|e| e.speed < cut as f64), "speed counted the resumed bytes");
+}
+
+#[tokio::test]
+async fn server_without_ranges_restarts_download() {
+    let root = TestRoot::new("no-ranges");
+    let cdn = MockCdn::start();
+    let archive = zip_of(&game_files("1.0.0"));
+    let checksum = sha256_hex(&archive);
+    cdn.serve("game.zip", archive.clone(), "application/zip");
+    cdn.knobs(|k| {
+        k.ranges = false;
+        k.truncate_next = Some(archive.len() / 3);
+    });
+
+    assert!(download::download_archive(&cdn.url("game.zip"), &checksum, "1.0.0", &NoProgress).await.is_err());
+    let staged = download::download_archive(&cdn.url("game.zip"), &checksum, "1.0.0", &NoProgress).await.unwrap();
+
+    assert_eq!(std::fs::read(&staged).unwrap(), archive);
+    assert_eq!(cdn.requests_for("game.zip").pop().unwrap().status, 200);
+    assert!(!root.cache_dir().join("game.tmp-1.0.0.zip").exists());
+}
+
+#[tokio::test]
+async fn service_unavailable_fails_without_partial_file() {
+    let root = TestRoot::new("download-503");
+    let cdn = MockCdn::start();
+    let archive = zip_of(&game_files("1.0.0"));
+    cdn.serve("game.zip", archive.clone(), "application/zip");
+    cdn.knobs(|k| k.fail_next = 1);
+
+    let result = download::download_and_install_game(&cdn.url("game.zip"), &sha256_hex(&archive), "1.0.0", &NoProgress).await;
+    assert!(result.is_err());
+    assert!(!root.cache_dir().join("game.tmp-1.0.0.zip").exists());
+    assert_eq!(game::get_local_version(), "0.0.0");
+
+    // Retrying once the CDN recovers works
+    download::download_and_install_game(&cdn.url("game.zip"), &sha256_hex(&archive), "1.0.0", &NoProgress).await.unwrap();
+    assert_eq!(game::get_local_version(), "1.0.0");
+}
+
+#[tokio::test]
+async fn slow_download_reports_progress_along_the_way() {
+    let _root = TestRoot::new("slow");
+    let cdn = MockCdn::start();
+    let archive = zip_of(&game_files("1.0.0"));
+    cdn.serve("game.zip", archive.clone(), "application/zip");
+    cdn.knobs(|k| k.chunk_delay = Some(Duration::from_millis(15)));
+
+    let sink = RecordingProgress::default();
+    download::download_archive(&cdn.url("game.zip"), &sha256_hex(&archive), "1.0.0", &sink).await.unwrap();
+
+    let downloading: Vec<_> = sink.events().into_iter().filter(|e| e.status == "Downloading").collect();
+    assert!(downloading.len() > 10, "only {} progress events", downloading.len());
+    assert!(downloading.windows(2).all(|w| w[0].downloaded <= w[1].downloaded));
+    assert!(downloading.iter().any(|e| e.speed > 0.0));
+    assert_eq!(downloading.last().unwrap().downloaded, archive.len() as u64);
+}
+
+#[tokio::test]
+async fn update_swaps_game_dir_and_drops_old_files() {
+    let root = TestRoot::new("swap");
+    let cdn = MockCdn::start();
+    let mut old_files = game_files("1.0.0");
+    old_files.push(("removed.dat", b"gone in 1.1.0".to_vec()));
+    let old = zip_of(&old_files);
+    let new = zip_of(&game_files("1.1.0"));
+    cdn.serve("game-1.0.0.zip", old.clone(), "application/zip");
+    cdn.serve("game-1.1.0.zip", new.clone(), "application/zip");
+
+    download::download_and_install_game(&cdn.url("game-1.0.0.zip"), &sha256_hex(&old), "1.0.0", &NoProgress).await.unwrap();
+    std::fs::write(root.game_dir().join("stray.log"), "left by the game").unwrap();
+    download::download_and_install_game(&cdn.url("game-1.1.0.zip"), &sha256_hex(&new), "1.1.0", &NoProgress).await.unwrap();
+
+    assert_eq!(game::get_local_version(), "1.1.0");
+    assert_eq!(read(&root.game_dir().join("game.exe")), "exe 1.1.0");
+    assert!(!root.game_dir().join("removed.dat").exists());
+    assert!(!root.game_dir().join("stray.log").exists());
+}
+
+#[tokio::test]
+async fn corrupt_archive_with_matching_checksum_keeps_old_install() {
+    let root = TestRoot::new("corrupt-archive");
+    let cdn = MockCdn::start();
+    let old = zip_of(&game_files("1.0.0"));
+    cdn.serve("game-1.0.0.zip", old.clone(), "application/zip");
+    download::download_and_install_game(&cdn.url("game-1.0.0.zip"), &sha256_hex(&old), "1.0.0", &NoProgress).await.unwrap();
+
+    // Published checksum matches the bytes, but the bytes aren't an archive
+    let garbage = noise(4096);
+    cdn.serve("game-1.1.0.zip", garbage.clone(), "application/zip");
+    let result = download::download_and_install_game(&cdn.url("game-1.1.0.zip"), &sha256_hex(&garbage), "1.1.0", &NoProgress).await;
+
+    assert!(result.is_err());
+    assert_eq!(game::get_local_version(), "1.0.0");
+    assert_eq!(read(&root.game_dir().join("game.exe")), "exe 1.0.0");
+}
+
+#[tokio::test]
+async fn staged_archive_is_installed_without_downloading() {
+    let root = TestRoot::new("staged");
+    let cdn = MockCdn::start();
+    let archive = zip_of(&game_files("1.0.0"));
+    let checksum = sha256_hex(&archive);
+    cdn.serve("game.zip", archive, "application/zip");
+
+    download::download_archive(&cdn.url("game.zip"), &checksum, "1.0.0", &NoProgress).await.unwrap();
+    assert!(download::is_staged("1.0.0", &checksum));
+    assert!(!root.game_dir().exists());
+
+    download::download_and_install_game(&cdn.url("game.zip"), &checksum, "1.0.0", &NoProgress).await.unwrap();
+    assert_eq!(cdn.requests_for("game.zip").len(), 1);
+    assert_eq!(game::get_local_version(), "1.0.0");
+}
+
+#[test]
+fn check_files_reports_each_kind_of_problem() {
+    let root = TestRoot::new("check-files");
+    let dir = root.game_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("ok.dat"), b"good").unwrap();
+    std::fs::write(dir.join("short.dat"), b"ab").unwrap();
+    std::fs::write(dir.join("changed.dat"), b"evil").unwrap();
+    let entry = |path: &str, content: &[u8]| FileHash { path: path.to_string(), size: content.len() as u64, sha256: sha256_hex(content) };
+    let list = FileList {
+        version: "1.0.0".to_string(),
+        files: vec![entry("ok.dat", b"good"), entry("short.dat", b"abc"), entry("changed.dat", b"good"), entry("gone.dat", b"x")],
+    };
+
+    let bad = download::check_files(&dir, &list);
+    assert_eq!(
+        bad,
+        vec![
+            ("short.dat".to_string(), FileProblem::SizeMismatch),
+            ("changed.dat".to_string(), FileProblem::HashMismatch),
+            ("gone.dat".to_string(), FileProblem::Missing),
+        ]
+    );
+}
@@ -0,0 +1,103 @@
+//! `--headless` exit codes against the mock CDN.
+mod common;
+
+use common::{make_zip, manifest_json, sha256_hex, MockCdn, TestRoot};
+use serde_json::Value;
+use tauri_app_lib::cli;
+
+fn headless(cdn: &MockCdn, command: &str) -> i32 {
+    let args: Vec<String> = ["launcher", "--headless", command, "--manifest-url", &cdn.url("manifest.json")]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    cli::run(&args).expect("headless args should be handled")
+}
+
+/// Serves game.zip for `version` and a manifest for it, with `extra` fields merged in
+fn publish(cdn: &MockCdn, version: &str, extra: Value) -> Vec<u8> {
+    let archive = make_zip(&[("game.exe", version.as_bytes()), ("data/a.dat", b"level data")]);
+    let checksum = sha256_hex(&archive);
+    cdn.serve("game.zip", archive.clone(), "application/zip");
+    let mut manifest: Value = serde_json::from_slice(&manifest_json(cdn, version, "game.zip", &checksum)).unwrap();
+    if let (Value::Object(m), Value::Object(extra)) = (&mut manifest, extra) {
+        m.extend(extra);
+    }
+    cdn.serve("manifest.json", serde_json::to_vec(&manifest).unwrap(), "application/json");
+    archive
+}
+
+#[test]
+fn unknown_or_missing_command_is_a_usage_error() {
+    let _root = TestRoot::new("headless-usage");
+    // Nothing listens there, the command must be rejected before any fetch
+    let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    assert_eq!(cli::run(&args(&["launcher", "--headless", "bogus", "--manifest-url", "http://127.0.0.1:9/m.json"])), Some(2));
+    assert_eq!(cli::run(&args(&["launcher", "--headless"])), Some(2));
+    assert_eq!(cli::run(&args(&["launcher", "--headless", "--manifest-url", "x"])), Some(2));
+}
+
+#[test]
+fn no_manifest_reachable_or_cached() {
+    let _root = TestRoot::new("headless-no-manifest");
+    let cdn = MockCdn::start();
+    assert_eq!(headless(&cdn, "status"), 3);
+}
+
+#[test]
+fn install_verify_update_flow() {
+    let root = TestRoot::new("headless-flow");
+    let cdn = MockCdn::start();
+    publish(&cdn, "1.0.0", Value::Null);
+
+    assert_eq!(headless(&cdn, "verify"), 4);
+    assert_eq!(headless(&cdn, "update"), 4);
+    assert_eq!(headless(&cdn, "status"), 0);
+    assert_eq!(headless(&cdn, "install"), 0);
+    assert_eq!(headless(&cdn, "verify"), 0);
+    assert_eq!(headless(&cdn, "update"), 0);
+
+    publish(&cdn, "1.1.0", Value::Null);
+    assert_eq!(headless(&cdn, "verify"), 5);
+    assert_eq!(headless(&cdn, "update"), 0);
+    assert_eq!(std::fs::read_to_string(root.game_dir().join("game.exe")).unwrap(), "1.1.0");
+}
+
+#[test]
+fn verify_checks_files_against_the_file_list() {
+    let root = TestRoot::new("headless-verify-files");
+    let cdn = MockCdn::start();
+    let list = serde_json::json!({
+        "version": "1.0.0",
+        "files": [
+            { "path": "game.exe", "size": 5, "sha256": sha256_hex(b"1.0.0") },
+            { "path": "data/a.dat", "size": 10, "sha256": sha256_hex(b"level data") },
+        ],
+    });
+    cdn.serve("files.json", serde_json::to_vec(&list).unwrap(), "application/json");
+    publish(&cdn, "1.0.0", serde_json::json!({ "file_list": cdn.url("files.json") }));
+
+    assert_eq!(headless(&cdn, "install"), 0);
+    assert_eq!(headless(&cdn, "verify"), 0);
+    std::fs::write(root.game_dir().join("data/a.dat"), b"level DATA").unwrap();
+    assert_eq!(headless(&cdn, "verify"), 6);
+}
+
+#[test]
+fn outdated_launcher_blocks_changes_but_not_status() {
+    let _root = TestRoot::new("headless-outdated");
+    let cdn = MockCdn::start();
+    publish(&cdn, "1.0.0", serde_json::json!({ "min_launcher_version": "999.0.0" }));
+
+    assert_eq!(headless(&cdn, "install"), 7);
+    assert_eq!(headless(&cdn, "status"), 0);
+}
+
+#[test]
+fn install_refuses_while_the_launcher_holds_the_lock() {
+    let _root = TestRoot::new("headless-locked");
+    let cdn = MockCdn::start();
+    publish(&cdn, "1.0.0", Value::Null);
+
+    let _lock = tauri_app_lib::single_instance::acquire().unwrap();
+    assert_eq!(headless(&cdn, "install"), 10);
+}
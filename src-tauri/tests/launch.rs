@@ -0,0 +1,99 @@
+//! Building launch plans: locked manifest args, profiles, servers and working directories.
+use std::path::Path;
+use tauri_app_lib::config::LaunchSettings;
+use tauri_app_lib::launcher_core::launch::{self, LaunchError, LaunchProfile};
+use tauri_app_lib::launcher_core::manifest::{JoinServer, ManifestLaunch};
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+fn manifest(locked: &[&str]) -> ManifestLaunch {
+    ManifestLaunch {
+        default_args: strings(&["-windowed"]),
+        locked_args: strings(locked),
+        profiles: vec![LaunchProfile {
+            name: "Safe".to_string(),
+            args: strings(&["-safe"]),
+            env: Default::default(),
+            working_dir: None,
+        }],
+        servers: vec![JoinServer { id: "asia1".to_string(), args: strings(&["-connect=asia1:7777"]) }],
+    }
+}
+
+fn settings(extra_args: &[&str]) -> LaunchSettings {
+    LaunchSettings { extra_args: strings(extra_args), ..Default::default() }
+}
+
+fn profile(name: &str, args: &[&str], working_dir: Option<&str>) -> LaunchProfile {
+    LaunchProfile { name: name.to_string(), args: strings(args), env: Default::default(), working_dir: working_dir.map(String::from) }
+}
+
+const GAME_DIR: &str = "/games/ganhrong/game";
+
+#[test]
+fn plan_puts_locked_args_first() {
+    let m = manifest(&["-server=prod"]);
+    let plan = launch::build_plan(Some(&m), &settings(&["-fps=144"]), Some("Safe"), Some("asia1"), Path::new(GAME_DIR)).unwrap();
+    assert_eq!(plan.args, strings(&["-server=prod", "-windowed", "-fps=144", "-safe", "-connect=asia1:7777"]));
+    assert_eq!(plan.working_dir, Path::new(GAME_DIR));
+}
+
+#[test]
+fn user_args_cannot_override_locked_value() {
+    let m = manifest(&["-server=prod"]);
+    for user in [&["-server=dev"][..], &["-server"], &["-SERVER=dev"], &["-server", "dev"]] {
+        let err = launch::build_plan(Some(&m), &settings(user), None, None, Path::new(GAME_DIR)).unwrap_err();
+        assert!(matches!(err, LaunchError::LockedArg(arg) if arg.eq_ignore_ascii_case(user[0])), "{:?} got through", user);
+    }
+}
+
+#[test]
+fn two_token_locked_arg_blocks_the_flag_but_not_its_value() {
+    let m = manifest(&["-server", "prod"]);
+    let err = launch::build_plan(Some(&m), &settings(&["-server=dev"]), None, None, Path::new(GAME_DIR)).unwrap_err();
+    assert!(matches!(err, LaunchError::LockedArg(_)));
+
+    // "prod" is the locked flag's value, not a flag of its own
+    let plan = launch::build_plan(Some(&m), &settings(&["-name", "prod"]), None, None, Path::new(GAME_DIR)).unwrap();
+    assert_eq!(plan.args, strings(&["-server", "prod", "-windowed", "-name", "prod"]));
+}
+
+#[test]
+fn profile_args_cannot_override_locked_args() {
+    let m = manifest(&["-anticheat=on"]);
+    let mut s = settings(&[]);
+    s.profiles.push(profile("No AC", &["-anticheat=off"], None));
+    let err = launch::build_plan(Some(&m), &s, Some("No AC"), None, Path::new(GAME_DIR)).unwrap_err();
+    assert!(matches!(err, LaunchError::LockedArg(arg) if arg == "-anticheat=off"));
+
+    // Also when it comes in as the default profile
+    s.default_profile = Some("No AC".to_string());
+    assert!(launch::build_plan(Some(&m), &s, None, None, Path::new(GAME_DIR)).is_err());
+}
+
+#[test]
+fn working_dir_stays_inside_game_dir() {
+    let m = manifest(&[]);
+    let mut s = settings(&[]);
+    s.profiles.push(profile("Bin", &[], Some("./bin/x64")));
+    let plan = launch::build_plan(Some(&m), &s, Some("Bin"), None, Path::new(GAME_DIR)).unwrap();
+    assert_eq!(plan.working_dir, Path::new(GAME_DIR).join("./bin/x64"));
+
+    for bad in ["../..", "bin/../../other", "/etc", "..\\windows"] {
+        let mut s = settings(&[]);
+        s.profiles.push(profile("Bad", &[], Some(bad)));
+        let err = launch::build_plan(Some(&m), &s, Some("Bad"), None, Path::new(GAME_DIR)).unwrap_err();
+        assert!(matches!(err, LaunchError::InvalidWorkingDir(_)), "{} was accepted", bad);
+    }
+}
+
+#[test]
+fn unknown_profile_and_server_are_errors() {
+    let m = manifest(&[]);
+    let err = launch::build_plan(Some(&m), &settings(&[]), Some("Nope"), None, Path::new(GAME_DIR)).unwrap_err();
+    assert!(matches!(err, LaunchError::UnknownProfile(_)));
+    let err = launch::build_plan(Some(&m), &settings(&[]), None, Some("eu9"), Path::new(GAME_DIR)).unwrap_err();
+    assert!(matches!(err, LaunchError::UnknownServer(_)));
+}
@@ -0,0 +1,177 @@
+//! Manifest fetching against the mock CDN: conditional requests, caching and errors.
+mod common;
+
+use common::{manifest_json, MockCdn, TestRoot};
+use tauri_app_lib::launcher_core::manifest::{self, ManifestFetch};
+use tauri_app_lib::launcher_core::net::{self, Connectivity};
+
+#[tokio::test]
+async fn fetch_caches_manifest_and_revalidates_with_etag() {
+    let root = TestRoot::new("manifest-etag");
+    let cdn = MockCdn::start();
+    cdn.serve("manifest.json", manifest_json(&cdn, "1.0.0", "game.zip", "00"), "application/json");
+
+    let m = manifest::fetch_manifest(&cdn.url("manifest.json")).await.unwrap();
+    assert_eq!(m.latest_version, "1.0.0");
+    assert!(root.cache_dir().join("manifest.json").exists());
+    assert_eq!(manifest::load_cached_manifest().unwrap().latest_version, "1.0.0");
+
+    // Same body: the cached ETag goes out and the server answers 304
+    let fetch = manifest::fetch_manifest_if_changed(&cdn.url("manifest.json")).await.unwrap();
+    assert!(matches!(fetch, ManifestFetch::Unchanged));
+    let last = cdn.requests_for("manifest.json").pop().unwrap();
+    assert!(last.headers.contains_key("if-none-match"));
+    assert_eq!(last.status, 304);
+
+    // New release: full body comes back with a diff
+    cdn.serve("manifest.json", manifest_json(&cdn, "1.1.0", "game.zip", "00"), "application/json");
+    match manifest::fetch_manifest_if_changed(&cdn.url("manifest.json")).await.unwrap() {
+        ManifestFetch::Changed { manifest, diff } => {
+            assert_eq!(manifest.latest_version, "1.1.0");
+            assert_eq!(diff.new_version.as_deref(), Some("1.1.0"));
+        }
+        ManifestFetch::Unchanged => panic!("expected a changed manifest"),
+    }
+}
+
+#[tokio::test]
+async fn unchanged_body_without_etag_is_unchanged() {
+    let _root = TestRoot::new("manifest-no-etag");
+    let cdn = MockCdn::start();
+    cdn.knobs(|k| k.etags = false);
+    cdn.serve("manifest.json", manifest_json(&cdn, "1.0.0", "game.zip", "00"), "application/json");
+
+    manifest::fetch_manifest(&cdn.url("manifest.json")).await.unwrap();
+    let fetch = manifest::fetch_manifest_if_changed(&cdn.url("manifest.json")).await.unwrap();
+    assert!(matches!(fetch, ManifestFetch::Unchanged));
+    assert_eq!(cdn.requests_for("manifest.json").last().unwrap().status, 200);
+}
+
+#[tokio::test]
+async fn server_error_is_reported_as_online() {
+    let _root = TestRoot::new("manifest-503");
+    let cdn = MockCdn::start();
+    cdn.serve("manifest.json", manifest_json(&cdn, "1.0.0", "game.zip", "00"), "application/json");
+    cdn.knobs(|k| k.fail_next = 1);
+
+    let err = manifest::fetch_manifest(&cdn.url("manifest.json")).await.unwrap_err();
+    // The host answered, so this isn't an offline error
+    assert_eq!(net::classify_error(err.as_ref()), Connectivity::Online);
+    assert!(manifest::load_cached_manifest().is_none());
+
+    // Next request goes through
+    assert!(manifest::fetch_manifest(&cdn.url("manifest.json")).await.is_ok());
+}
+
+#[tokio::test]
+async fn error_status_keeps_cached_manifest_and_validators() {
+    let root = TestRoot::new("manifest-error-keeps-cache");
+    let cdn = MockCdn::start();
+    cdn.serve("manifest.json", manifest_json(&cdn, "1.0.0", "game.zip", "00"), "application/json");
+    manifest::fetch_manifest(&cdn.url("manifest.json")).await.unwrap();
+    let meta = common::read(&root.cache_dir().join("manifest.meta.json"));
+
+    cdn.serve("manifest.json", manifest_json(&cdn, "1.1.0", "game.zip", "00"), "application/json");
+    cdn.knobs(|k| k.fail_next = 2);
+    assert!(manifest::fetch_manifest_if_changed(&cdn.url("manifest.json")).await.is_err());
+    assert!(manifest::fetch_manifest(&cdn.url("manifest.json")).await.is_err());
+    assert_eq!(common::read(&root.cache_dir().join("manifest.meta.json")), meta);
+    assert_eq!(manifest::load_cached_manifest().unwrap().latest_version, "1.0.0");
+
+    assert!(matches!(
+        manifest::fetch_manifest_if_changed(&cdn.url("manifest.json")).await.unwrap(),
+        ManifestFetch::Changed { .. }
+    ));
+    assert!(manifest::fetch_manifest(&cdn.url("missing.json")).await.is_err());
+}
+
+#[tokio::test]
+async fn captive_portal_is_detected_by_check_endpoint() {
+    let _root = TestRoot::new("manifest-captive");
+    let cdn = MockCdn::start();
+    cdn.serve("manifest.json", manifest_json(&cdn, "1.0.0", "game.zip", "00"), "application/json");
+    cdn.serve("generate_204", Vec::new(), "text/plain");
+    // Nothing listens on the discard port, like a host the portal won't let through
+    let unreachable = "http://127.0.0.1:9/manifest.json";
+    assert_eq!(net::probe_with(unreachable, &cdn.url("generate_204")).await, Connectivity::Offline);
+
+    // A portal answers the check endpoint with its login page
+    cdn.serve("generate_204", b"<html>Log in to Wi-Fi</html>".to_vec(), "text/html");
+    assert_eq!(net::probe_with(unreachable, &cdn.url("generate_204")).await, Connectivity::CaptivePortal);
+    assert_eq!(net::probe_with(unreachable, &cdn.url("missing")).await, Connectivity::CaptivePortal);
+
+    // The check endpoint is only asked when the manifest host fails
+    assert_eq!(net::probe_with(&cdn.url("manifest.json"), &cdn.url("generate_204")).await, Connectivity::Online);
+    assert_eq!(cdn.requests_for("generate_204").len(), 2);
+}
+
+#[tokio::test]
+async fn html_manifest_is_not_a_captive_portal() {
+    let _root = TestRoot::new("manifest-html");
+    let cdn = MockCdn::start();
+    cdn.serve("manifest.json", b"<html>Oops</html>".to_vec(), "text/html");
+
+    // The host answered, the manifest is broken
+    let err = manifest::fetch_manifest(&cdn.url("manifest.json")).await.unwrap_err();
+    assert_eq!(net::classify_error(err.as_ref()), Connectivity::Online);
+}
+
+#[tokio::test]
+async fn unreachable_host_is_offline() {
+    let _root = TestRoot::new("manifest-offline");
+    // Bind and drop to get a port nothing listens on
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let url = format!("http://127.0.0.1:{}/manifest.json", port);
+
+    let err = manifest::fetch_manifest(&url).await.unwrap_err();
+    assert_eq!(net::classify_error(err.as_ref()), Connectivity::Offline);
+}
+
+#[test]
+fn v1_manifest_is_migrated() {
+    let body = br#"{
+        "game_name": "Test Game",
+        "latest_version": "1.0.0",
+        "game_zip": "http://localhost/game.zip",
+        "checksum": "sha256:00",
+        "background": "http://localhost/bg.png"
+    }"#;
+    let m = manifest::parse_manifest(body).unwrap();
+    assert_eq!(m.schema_version, manifest::CURRENT_SCHEMA_VERSION);
+    assert_eq!(m.presentation.backgrounds, vec!["http://localhost/bg.png".to_string()]);
+
+    // 0 isn't a schema we ever wrote, treat it like 1 instead of falling over
+    let body = br#"{ "schema_version": 0, "game_name": "Test Game", "latest_version": "1.0.0",
+        "game_zip": "http://localhost/game.zip", "checksum": "sha256:00", "background": "bg.png" }"#;
+    let m = manifest::parse_manifest(body).unwrap();
+    assert_eq!(m.schema_version, manifest::CURRENT_SCHEMA_VERSION);
+    assert_eq!(m.presentation.backgrounds, vec!["bg.png".to_string()]);
+}
+
+#[test]
+fn malformed_optional_sections_are_dropped() {
+    let body = br#"{
+        "schema_version": 2,
+        "game_name": "Test Game",
+        "latest_version": "1.0.0",
+        "game_zip": "http://localhost/game.zip",
+        "checksum": "sha256:00",
+        "news": [
+            { "title": "Patch notes", "image": "n.png", "date": "2024-05-01" },
+            { "title": 42 }
+        ],
+        "sidebar_links": "not a map",
+        "preload": { "version": "1.1.0" },
+        "languages": ["vi", "en"]
+    }"#;
+    let m = manifest::parse_manifest(body).unwrap();
+    assert_eq!(m.presentation.news.len(), 1);
+    assert_eq!(m.presentation.news[0].title, "Patch notes");
+    assert!(m.presentation.sidebar_links.is_none());
+    assert!(m.preload.is_none());
+    assert_eq!(m.presentation.languages, vec!["vi".to_string(), "en".to_string()]);
+
+    // The core fields stay strict
+    let body = br#"{ "schema_version": 2, "game_name": "Test Game", "latest_version": "1.0.0", "checksum": "sha256:00" }"#;
+    assert!(manifest::parse_manifest(body).is_err());
+}
@@ -0,0 +1,40 @@
+//! Portable vs installed directory layout.
+use std::path::PathBuf;
+use tauri_app_lib::paths::{self, LauncherPaths, PathMode};
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[test]
+fn installed_mode_follows_xdg_dirs() {
+    std::env::set_var("XDG_DATA_HOME", "/xdg/data");
+    std::env::set_var("XDG_CONFIG_HOME", "/xdg/config");
+    std::env::set_var("XDG_CACHE_HOME", "/xdg/cache");
+
+    let p = LauncherPaths::installed().unwrap();
+    assert_eq!(p.mode, PathMode::Installed);
+    assert_eq!(p.game_dir(), PathBuf::from("/xdg/data/ganhrong-launcher/game"));
+    assert_eq!(p.config_file(), PathBuf::from("/xdg/config/ganhrong-launcher/config.json"));
+    assert_eq!(p.cache_dir, PathBuf::from("/xdg/cache/ganhrong-launcher"));
+    assert_eq!(p.logs_dir, PathBuf::from("/xdg/data/ganhrong-launcher/logs"));
+}
+
+#[test]
+fn portable_mode_keeps_everything_under_one_folder() {
+    let root = PathBuf::from("/games/launcher");
+    let p = LauncherPaths::portable(&root);
+    assert_eq!(p.mode, PathMode::Portable);
+    assert_eq!(p.game_dir(), root.join("game"));
+    assert_eq!(p.config_file(), root.join("config.json"));
+    assert_eq!(p.cache_dir, root.join("cache"));
+    assert_eq!(p.logs_dir, root.join("logs"));
+}
+
+#[test]
+fn override_replaces_detected_paths() {
+    let root = std::env::temp_dir().join("launcher-test-override");
+    paths::set_root_override(Some(root.clone()));
+    assert_eq!(paths::get_game_dir(), root.join("game"));
+    assert_eq!(paths::get_config_path(), root.join("config.json"));
+
+    paths::set_root_override(None);
+    assert_ne!(paths::get_game_dir(), root.join("game"));
+}
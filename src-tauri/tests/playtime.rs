@@ -0,0 +1,94 @@
+//! Play history persistence and the stats built from it.
+mod common;
+
+use std::fs;
+use common::TestRoot;
+use tauri_app_lib::playtime::{append_session, compute_stats, get_play_stats, load_sessions, record_unfinished, Session};
+
+fn session(version: &str, ended_at: &str, duration_secs: u64) -> Session {
+    Session {
+        started_at: ended_at.to_string(),
+        ended_at: ended_at.to_string(),
+        duration_secs,
+        version: version.to_string(),
+        exit_code: Some(0),
+        unfinished: false,
+    }
+}
+
+#[test]
+fn stats_sum_per_version_and_pick_latest_end() {
+    let stats = compute_stats(&[
+        session("1.0.0", "2026-01-02T10:00:00+00:00", 600),
+        // Later instant despite the earlier wall clock, offsets must be compared properly
+        session("1.1.0", "2026-01-02T09:00:00-05:00", 120),
+        session("1.0.0", "not a date", 30),
+    ]);
+    assert_eq!(stats.total_secs, 750);
+    assert_eq!(stats.sessions, 3);
+    assert_eq!(stats.per_version["1.0.0"].total_secs, 630);
+    assert_eq!(stats.per_version["1.0.0"].sessions, 2);
+    assert_eq!(stats.per_version["1.1.0"].sessions, 1);
+    assert_eq!(stats.last_played.as_deref(), Some("2026-01-02T09:00:00-05:00"));
+
+    let empty = compute_stats(&[]);
+    assert_eq!(empty.sessions, 0);
+    assert_eq!(empty.last_played, None);
+}
+
+#[test]
+fn sessions_persist_and_append() {
+    let root = TestRoot::new("playtime-persist");
+    assert!(load_sessions().is_empty());
+    append_session(session("1.0.0", "2026-01-02T10:00:00+00:00", 600)).unwrap();
+    append_session(session("1.0.0", "2026-01-03T10:00:00+00:00", 60)).unwrap();
+
+    let sessions = load_sessions();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[1].duration_secs, 60);
+    assert_eq!(get_play_stats().total_secs, 660);
+    assert!(!root.dir.join("play_history.json.tmp").exists());
+}
+
+#[test]
+fn corrupt_history_is_moved_aside_not_overwritten() {
+    let root = TestRoot::new("playtime-corrupt");
+    fs::write(root.dir.join("play_history.json"), "[{\"started_at\": ").unwrap();
+
+    append_session(session("1.0.0", "2026-01-02T10:00:00+00:00", 600)).unwrap();
+
+    let aside: Vec<_> = fs::read_dir(&root.dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with("play_history.json.bad-"))
+        .collect();
+    assert_eq!(aside.len(), 1);
+    assert_eq!(fs::read_to_string(aside[0].path()).unwrap(), "[{\"started_at\": ");
+    assert_eq!(load_sessions().len(), 1);
+}
+
+#[test]
+fn unfinished_session_counts_without_time() {
+    let _root = TestRoot::new("playtime-unfinished");
+    record_unfinished("1.0.0", "2026-01-02T10:00:00+00:00").unwrap();
+
+    let sessions = load_sessions();
+    assert!(sessions[0].unfinished);
+    let stats = get_play_stats();
+    assert_eq!(stats.sessions, 1);
+    assert_eq!(stats.total_secs, 0);
+    assert_eq!(stats.last_played.as_deref(), Some("2026-01-02T10:00:00+00:00"));
+}
+
+#[test]
+fn old_history_without_unfinished_field_loads() {
+    let root = TestRoot::new("playtime-legacy");
+    fs::write(
+        root.dir.join("play_history.json"),
+        r#"[{"started_at":"a","ended_at":"2026-01-02T10:00:00+00:00","duration_secs":5,"version":"1.0.0","exit_code":0}]"#,
+    )
+    .unwrap();
+    let sessions = load_sessions();
+    assert_eq!(sessions.len(), 1);
+    assert!(!sessions[0].unfinished);
+}
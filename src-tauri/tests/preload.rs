@@ -0,0 +1,109 @@
+//! Preload staging, unlock and the release-day install decision.
+mod common;
+
+use common::{make_zip, manifest_json, sha256_hex, MockCdn, TestRoot};
+use tauri_app_lib::launcher_core::download;
+use tauri_app_lib::launcher_core::manifest::GameManifest;
+use tauri_app_lib::launcher_core::progress::NoProgress;
+use tauri_app_lib::preload::{next_action, PreloadAction};
+
+const NOW: i64 = 1_800_000_000;
+
+/// Manifest with `latest` current and optionally a preload of `upcoming`, both archives on the CDN
+fn manifest(cdn: &MockCdn, latest: &str, upcoming: Option<(&str, i64)>) -> GameManifest {
+    let mut value: serde_json::Value = serde_json::from_slice(&manifest_json(cdn, latest, &archive_name(latest), &publish(cdn, latest))).unwrap();
+    if let Some((version, unlock_at)) = upcoming {
+        value["preload"] = serde_json::json!({
+            "version": version,
+            "archive": cdn.url(&archive_name(version)),
+            "checksum": publish(cdn, version),
+            "unlock_at": unlock_at,
+        });
+    }
+    serde_json::from_value(value).unwrap()
+}
+
+fn archive_name(version: &str) -> String {
+    format!("game-{}.zip", version)
+}
+
+fn publish(cdn: &MockCdn, version: &str) -> String {
+    let archive = make_zip(&[("game.exe", version.as_bytes())]);
+    let checksum = sha256_hex(&archive);
+    cdn.serve(&archive_name(version), archive, "application/zip");
+    checksum
+}
+
+async fn stage_preload(m: &GameManifest) {
+    let preload = m.preload.as_ref().unwrap();
+    download::download_archive(&preload.archive, &preload.checksum, &preload.version, &NoProgress).await.unwrap();
+    download::mark_preloaded(&preload.version).unwrap();
+}
+
+#[tokio::test]
+async fn stages_only_when_allowed_and_not_yet_staged() {
+    let _root = TestRoot::new("preload-stage");
+    let cdn = MockCdn::start();
+    let m = manifest(&cdn, "1.0.0", Some(("2.0.0", NOW + 3600)));
+
+    assert_eq!(next_action(&m, "1.0.0", NOW, false), PreloadAction::None);
+    let PreloadAction::Stage(preload) = next_action(&m, "1.0.0", NOW, true) else {
+        panic!("an allowed preload should stage");
+    };
+    assert_eq!(preload.version, "2.0.0");
+
+    stage_preload(&m).await;
+    assert!(download::is_preloaded("2.0.0", &preload.checksum));
+    assert_eq!(next_action(&m, "1.0.0", NOW, true), PreloadAction::None);
+    // Already on the preloaded version, nothing to stage
+    assert_eq!(next_action(&m, "2.0.0", NOW, true), PreloadAction::None);
+}
+
+#[tokio::test]
+async fn installs_once_released_and_unlocked() {
+    let _root = TestRoot::new("preload-release");
+    let cdn = MockCdn::start();
+    let before = manifest(&cdn, "1.0.0", Some(("2.0.0", NOW + 3600)));
+    stage_preload(&before).await;
+
+    // Manifest flipped early but the unlock time hasn't come
+    let flipped = manifest(&cdn, "2.0.0", Some(("2.0.0", NOW + 3600)));
+    assert_eq!(next_action(&flipped, "1.0.0", NOW, false), PreloadAction::None);
+    assert_eq!(next_action(&flipped, "1.0.0", NOW + 3600, false), PreloadAction::Install);
+
+    // Publisher already dropped the preload block, the staged archive still gets installed
+    let released = manifest(&cdn, "2.0.0", None);
+    assert_eq!(next_action(&released, "1.0.0", NOW, false), PreloadAction::Install);
+    assert_eq!(next_action(&released, "2.0.0", NOW, false), PreloadAction::None);
+}
+
+#[tokio::test]
+async fn download_only_staging_is_not_installed_as_a_preload() {
+    let _root = TestRoot::new("preload-download-only");
+    let cdn = MockCdn::start();
+    let m = manifest(&cdn, "2.0.0", None);
+    download::download_archive(&m.game_zip, &m.checksum, "2.0.0", &NoProgress).await.unwrap();
+
+    assert!(download::is_staged("2.0.0", &m.checksum));
+    assert_eq!(next_action(&m, "1.0.0", NOW, true), PreloadAction::None);
+}
+
+#[tokio::test]
+async fn prune_drops_stale_archives_markers_and_partials() {
+    let root = TestRoot::new("preload-prune");
+    let cdn = MockCdn::start();
+    let m = manifest(&cdn, "1.0.0", Some(("2.0.0", NOW)));
+    stage_preload(&m).await;
+    let cache = root.cache_dir();
+    std::fs::write(cache.join("game.tmp-1.5.0.zip"), b"partial").unwrap();
+    std::fs::write(cache.join("game.tmp-1.0.0.zip"), b"partial").unwrap();
+
+    download::prune_staged(&["1.0.0"]);
+    let mut left: Vec<String> = std::fs::read_dir(&cache)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("game"))
+        .collect();
+    left.sort();
+    assert_eq!(left, vec!["game.tmp-1.0.0.zip"]);
+}
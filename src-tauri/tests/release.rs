@@ -0,0 +1,157 @@
+//! Release tool output: archives that install, file lists, deltas and manifest signatures.
+mod common;
+
+use std::fs;
+use std::path::Path;
+use common::{read, MockCdn, TestRoot};
+use ed25519_dalek::SigningKey;
+use tauri_app_lib::launcher_core::download;
+use tauri_app_lib::launcher_core::game;
+use tauri_app_lib::launcher_core::manifest::{self, FileList};
+use tauri_app_lib::launcher_core::progress::NoProgress;
+use tauri_app_lib::launcher_core::signing;
+use tauri_app_lib::release::{build_release, ArchiveFormat, ReleaseOptions};
+
+const SEED: [u8; 32] = [7; 32];
+
+fn write_build(dir: &Path, files: &[(&str, &str)]) {
+    for (rel, content) in files {
+        let path = dir.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
+
+fn options(root: &TestRoot, build: &str, version: &str, format: ArchiveFormat) -> ReleaseOptions {
+    ReleaseOptions {
+        build_dir: root.dir.join(build),
+        version: version.to_string(),
+        out_dir: root.dir.join("out"),
+        name: "ganhrong".to_string(),
+        base_url: "https://cdn.example.com/releases/".to_string(),
+        format,
+        manifest: None,
+        previous: None,
+        signing_key: None,
+    }
+}
+
+fn round_trip(format: ArchiveFormat) {
+    let root = TestRoot::new(&format!("release-{}", format.extension()));
+    write_build(&root.dir.join("build"), &[("game.exe", "exe"), ("data/maps/a.dat", "map a"), ("version.txt", "stale")]);
+
+    let summary = build_release(&options(&root, "build", "1.2.0", format)).unwrap();
+    assert_eq!(summary.file_count, 2);
+    assert!(summary.archive.to_string_lossy().ends_with(&format!("ganhrong-v1.2.0.{}", format.extension())));
+
+    let list: FileList = serde_json::from_str(&read(&summary.file_list)).unwrap();
+    let paths: Vec<&str> = list.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["data/maps/a.dat", "game.exe"]);
+
+    let m = manifest::parse_manifest(read(&summary.manifest).as_bytes()).unwrap();
+    assert_eq!(m.latest_version, "1.2.0");
+    assert_eq!(m.game_zip, format!("https://cdn.example.com/releases/ganhrong-v1.2.0.{}", format.extension()));
+    assert_eq!(m.checksum, format!("sha256:{}", download::sha256_file(&summary.archive).unwrap()));
+    assert_eq!(m.file_list.as_deref(), Some("https://cdn.example.com/releases/ganhrong-v1.2.0.files.json"));
+
+    // What the launcher does with it
+    download::install_archive(&summary.archive, "1.2.0", &NoProgress).unwrap();
+    assert_eq!(read(&root.game_dir().join("data/maps/a.dat")), "map a");
+    assert_eq!(game::get_local_version(), "1.2.0");
+    assert!(download::check_files(&root.game_dir(), &list).is_empty());
+}
+
+#[test]
+fn zip_release_installs() {
+    round_trip(ArchiveFormat::Zip);
+}
+
+#[test]
+fn tar_zst_release_installs() {
+    round_trip(ArchiveFormat::TarZst);
+}
+
+#[test]
+fn delta_has_only_changed_files() {
+    let root = TestRoot::new("release-delta");
+    write_build(&root.dir.join("v1"), &[("game.exe", "exe 1"), ("same.dat", "same"), ("old.dat", "old")]);
+    let first = build_release(&options(&root, "v1", "1.0.0", ArchiveFormat::Zip)).unwrap();
+    assert!(first.delta.is_none());
+
+    write_build(&root.dir.join("v2"), &[("game.exe", "exe 2"), ("same.dat", "same"), ("new/added.dat", "new")]);
+    let mut second = options(&root, "v2", "1.1.0", ArchiveFormat::Zip);
+    second.previous = Some(first.file_list.clone());
+    let delta = build_release(&second).unwrap().delta.unwrap();
+
+    assert_eq!((delta.from.as_str(), delta.to.as_str()), ("1.0.0", "1.1.0"));
+    assert_eq!(delta.changed, vec!["game.exe", "new/added.dat"]);
+    assert_eq!(delta.removed, vec!["old.dat"]);
+    assert_eq!(delta.archive, "https://cdn.example.com/releases/ganhrong-v1.0.0-to-v1.1.0.zip");
+
+    let delta_path = root.dir.join("out/ganhrong-v1.0.0-to-v1.1.0.zip");
+    assert_eq!(delta.checksum, format!("sha256:{}", download::sha256_file(&delta_path).unwrap()));
+    let archive = zip::ZipArchive::new(fs::File::open(&delta_path).unwrap()).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(names, vec!["game.exe", "new/added.dat"]);
+    assert!(root.dir.join("out/ganhrong-v1.0.0-to-v1.1.0.delta.json").exists());
+}
+
+#[test]
+fn keeps_manifest_sections_and_drops_released_preload() {
+    let root = TestRoot::new("release-manifest");
+    write_build(&root.dir.join("build"), &[("game.exe", "exe")]);
+    fs::write(root.dir.join("current.json"), serde_json::json!({
+        "schema_version": 2,
+        "game_name": "Ganhrong",
+        "latest_version": "1.0.0",
+        "game_zip": "https://old/game.zip",
+        "checksum": "00",
+        "news": [{ "title": "Patch notes", "image": "https://example.com/n.png", "date": "2026-01-01" }],
+        "preload": { "version": "1.1.0", "archive": "https://old/pre.zip", "checksum": "11", "unlock_at": 0 },
+    }).to_string()).unwrap();
+    let mut opts = options(&root, "build", "1.1.0", ArchiveFormat::Zip);
+    opts.manifest = Some(root.dir.join("current.json"));
+
+    let summary = build_release(&opts).unwrap();
+    let m = manifest::parse_manifest(read(&summary.manifest).as_bytes()).unwrap();
+    assert_eq!(m.game_name, "Ganhrong");
+    assert_eq!(m.presentation.news.len(), 1);
+    assert!(m.preload.is_none());
+}
+
+#[test]
+fn bad_input_is_rejected() {
+    let root = TestRoot::new("release-bad-input");
+    fs::create_dir_all(root.dir.join("empty")).unwrap();
+    assert!(build_release(&options(&root, "empty", "1.0.0", ArchiveFormat::Zip)).is_err());
+    write_build(&root.dir.join("build"), &[("game.exe", "exe")]);
+    assert!(build_release(&options(&root, "build", "v1", ArchiveFormat::Zip)).is_err());
+}
+
+#[tokio::test]
+async fn signed_manifest_verifies_against_the_public_key() {
+    let root = TestRoot::new("release-signed");
+    write_build(&root.dir.join("build"), &[("game.exe", "exe")]);
+    fs::write(root.dir.join("seed.hex"), hex::encode(SEED)).unwrap();
+    let mut opts = options(&root, "build", "1.0.0", ArchiveFormat::Zip);
+    opts.signing_key = Some(root.dir.join("seed.hex"));
+
+    let summary = build_release(&opts).unwrap();
+    let public_key = hex::encode(SigningKey::from_bytes(&SEED).verifying_key().to_bytes());
+    let body = fs::read(&summary.manifest).unwrap();
+    let signature = read(summary.signature.as_ref().unwrap());
+    assert!(signing::verify_with(&public_key, &body, &signature).unwrap());
+    assert!(!signing::verify_with(&public_key, b"{\"tampered\":true}", &signature).unwrap());
+
+    // What the launcher fetches: manifest.json and manifest.json.sig next to it
+    let cdn = MockCdn::start();
+    cdn.serve("manifest.json", body.clone(), "application/json");
+    cdn.serve("manifest.json.sig", signature.into_bytes(), "text/plain");
+    let url = cdn.url("manifest.json");
+    manifest::check_signature_with(&url, &body, &public_key).await.unwrap();
+
+    let err = manifest::check_signature_with(&url, b"{}", &public_key).await.unwrap_err();
+    assert!(err.is::<manifest::InvalidSignature>());
+    assert!(manifest::check_signature_with(&cdn.url("unsigned.json"), &body, &public_key).await.is_err());
+}
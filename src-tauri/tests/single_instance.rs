@@ -0,0 +1,49 @@
+//! Second launches find the lock and hand their args to the first.
+mod common;
+
+use std::sync::mpsc;
+use std::time::Duration;
+use common::TestRoot;
+use tauri_app_lib::single_instance::{self, Instance};
+
+#[test]
+fn second_launch_forwards_args_to_first() {
+    let root = TestRoot::new("single-instance");
+
+    let Instance::Primary(lock) = single_instance::acquire().unwrap() else {
+        panic!("first launch should get the lock");
+    };
+    assert!(matches!(single_instance::acquire().unwrap(), Instance::Secondary));
+
+    let (tx, rx) = mpsc::channel();
+    lock.listen(move |args| tx.send(args).unwrap());
+
+    let args = vec!["launcher".to_string(), "ganhrong://launch".to_string()];
+    single_instance::forward(&args).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), args);
+
+    // Someone who can't read the port file doesn't know the token
+    let port: u16 = std::fs::read_to_string(root.dir.join("launcher.port")).unwrap()
+        .split(' ').next().unwrap().parse().unwrap();
+    let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+    std::io::Write::write_all(&mut stream, b"{\"token\":\"guess\",\"args\":[\"x\"]}\n").unwrap();
+    drop(stream);
+    assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+}
+
+#[test]
+fn launch_during_restart_takes_over_the_lock() {
+    let _root = TestRoot::new("single-instance-restart");
+    let Instance::Primary(old) = single_instance::acquire().unwrap() else {
+        panic!("first launch should get the lock");
+    };
+    // The old launcher never answers and lets go of the lock shortly after, like app.restart()
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(500));
+        drop(old);
+    });
+
+    let args = vec!["launcher".to_string()];
+    assert!(matches!(single_instance::acquire().unwrap(), Instance::Secondary));
+    assert!(single_instance::forward_or_take_over(&args).unwrap().is_some());
+}
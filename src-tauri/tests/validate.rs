@@ -0,0 +1,111 @@
+//! `--validate-manifest` reports against the mock CDN.
+mod common;
+
+use common::{make_zip, manifest_json, sha256_hex, MockCdn};
+use serde_json::Value;
+use tauri_app_lib::validate::{validate_manifest, Severity, ValidateOptions, ValidationReport};
+
+const OFFLINE: ValidateOptions = ValidateOptions { check_urls: false, hash_archive: false };
+
+/// The standard test manifest with `extra` fields merged over it
+fn manifest(cdn: &MockCdn, checksum: &str, extra: Value) -> Vec<u8> {
+    let mut value: Value = serde_json::from_slice(&manifest_json(cdn, "1.0.0", "game.zip", checksum)).unwrap();
+    if let (Value::Object(m), Value::Object(extra)) = (&mut value, extra) {
+        m.extend(extra);
+    }
+    serde_json::to_vec(&value).unwrap()
+}
+
+fn issue(report: &ValidationReport, field: &str) -> Option<Severity> {
+    report.issues.iter().find(|i| i.field == field).map(|i| i.severity)
+}
+
+#[tokio::test]
+async fn not_json_is_an_error() {
+    let report = validate_manifest(b"{ nope", &OFFLINE).await;
+    assert!(!report.ok);
+    assert_eq!(issue(&report, ""), Some(Severity::Error));
+}
+
+#[tokio::test]
+async fn malformed_sections_are_warnings() {
+    let cdn = MockCdn::start();
+    let body = manifest(&cdn, &"a".repeat(64), serde_json::json!({
+        "news": [{ "title": "No image or date" }],
+        "preload": { "version": "2.0.0" },
+        "launch": { "locked_args": "-server=prod" },
+        "sidebar_links": { "store": 5 },
+    }));
+    let report = validate_manifest(&body, &OFFLINE).await;
+
+    for field in ["news", "preload", "launch", "sidebar_links"] {
+        assert_eq!(issue(&report, field), Some(Severity::Warning), "{} should be flagged", field);
+    }
+    // The launcher drops them and carries on, so players can still install
+    assert!(report.ok);
+}
+
+#[tokio::test]
+async fn bad_versions_and_checksums_are_errors() {
+    let cdn = MockCdn::start();
+    let mut body: Value = serde_json::from_slice(&manifest(&cdn, "sha256:xyz", serde_json::json!({
+        "min_launcher_version": "soon",
+    })))
+    .unwrap();
+    body["latest_version"] = "v1".into();
+    let report = validate_manifest(&serde_json::to_vec(&body).unwrap(), &OFFLINE).await;
+
+    assert!(!report.ok);
+    assert_eq!(issue(&report, "latest_version"), Some(Severity::Error));
+    assert_eq!(issue(&report, "checksum"), Some(Severity::Error));
+    assert_eq!(issue(&report, "min_launcher_version"), Some(Severity::Error));
+    assert_eq!(issue(&report, "launcher_update"), Some(Severity::Warning));
+}
+
+#[tokio::test]
+async fn unreachable_urls_are_reported_by_how_much_they_matter() {
+    let cdn = MockCdn::start();
+    cdn.serve("game.zip", b"zip".to_vec(), "application/zip");
+    let body = manifest(&cdn, &"a".repeat(64), serde_json::json!({ "backgrounds": [cdn.url("missing.png")] }));
+    let options = ValidateOptions { check_urls: true, hash_archive: false };
+
+    let report = validate_manifest(&body, &options).await;
+    assert!(report.ok);
+    assert_eq!(issue(&report, "backgrounds[0]"), Some(Severity::Warning));
+    let bg = report.urls.iter().find(|u| u.field == "backgrounds[0]").unwrap();
+    assert_eq!((bg.status, bg.ok), (Some(404), false));
+    assert!(cdn.requests_for("game.zip").iter().all(|r| r.method == "HEAD"));
+
+    // Without the archive nobody can install
+    cdn.knobs(|k| k.fail_next = 2);
+    let report = validate_manifest(&body, &options).await;
+    assert!(!report.ok);
+    assert_eq!(issue(&report, "game_zip"), Some(Severity::Error));
+}
+
+#[tokio::test]
+async fn archive_hash_is_compared_with_the_checksum() {
+    let cdn = MockCdn::start();
+    let archive = make_zip(&[("game.exe", b"exe")]);
+    let checksum = sha256_hex(&archive);
+    cdn.serve("game.zip", archive, "application/zip");
+    let options = ValidateOptions { check_urls: false, hash_archive: true };
+
+    let report = validate_manifest(&manifest(&cdn, &checksum, Value::Null), &options).await;
+    assert!(report.ok);
+    assert!(report.archive.as_ref().unwrap().ok);
+
+    let report = validate_manifest(&manifest(&cdn, &"b".repeat(64), Value::Null), &options).await;
+    assert!(!report.ok);
+    assert_eq!(issue(&report, "checksum"), Some(Severity::Error));
+    let check = report.archive.unwrap();
+    assert_eq!(check.actual.as_deref(), Some(checksum.as_str()));
+
+    cdn.knobs(|k| k.fail_next = 1);
+    let report = validate_manifest(&manifest(&cdn, &checksum, Value::Null), &options).await;
+    assert!(!report.ok);
+    assert!(report.archive.unwrap().error.is_some());
+
+    let temp = std::env::temp_dir().join(format!("manifest-validate-{}.zip", std::process::id()));
+    assert!(!temp.exists());
+}
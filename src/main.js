@@ -16,6 +16,9 @@ const STRINGS = {
         "update": "Update",
         "checking": "Checking...",
         "offline": "Offline",
+        "maintenance": "Maintenance",
+        "running": "Running",
+        "launcherOutdated": "Updating launcher...",
         "downloading": "Downloading...",
         "pause": "Pause",
         "resume": "Resume",
@@ -23,7 +26,8 @@ const STRINGS = {
         "language": "Language",
         "closeWindow": "Close Window",
         "minimizeToTray": "Minimize to System Tray",
-        "exitLauncher": "Exit Launcher"
+        "exitLauncher": "Exit Launcher",
+        "launchAtStartup": "Launch at Startup"
     },
     "vi": {
         "download": "Tải Game",
@@ -31,6 +35,9 @@ const STRINGS = {
         "update": "Cập nhật",
         "checking": "Đang kiểm tra...",
         "offline": "Không có mạng",
+        "maintenance": "Bảo trì",
+        "running": "Đang chạy",
+        "launcherOutdated": "Đang cập nhật launcher...",
         "downloading": "Đang tải...",
         "pause": "Tạm dừng",
         "resume": "Tiếp tục",
@@ -38,7 +45,8 @@ const STRINGS = {
         "language": "Ngôn ngữ",
         "closeWindow": "Đóng cửa sổ",
         "minimizeToTray": "Thu nhỏ xuống khay",
-        "exitLauncher": "Thoát Launcher"
+        "exitLauncher": "Thoát Launcher",
+        "launchAtStartup": "Khởi động cùng máy tính"
     },
     "jp": {
         "download": "ダウンロード",
@@ -46,6 +54,9 @@ const STRINGS = {
         "update": "更新",
         "checking": "確認中...",
         "offline": "オフライン",
+        "maintenance": "メンテナンス中",
+        "running": "プレイ中",
+        "launcherOutdated": "ランチャーを更新中...",
         "downloading": "ダウンロード中...",
         "pause": "一時停止",
         "resume": "再開",
@@ -53,7 +64,8 @@ const STRINGS = {
         "language": "言語",
         "closeWindow": "ウィンドウを閉じる",
         "minimizeToTray": "トレイに最小化",
-        "exitLauncher": "ランチャーを終了"
+        "exitLauncher": "ランチャーを終了",
+        "launchAtStartup": "ログイン時に起動"
     },
     "zh": {
         "download": "下载",
@@ -61,6 +73,9 @@ const STRINGS = {
         "update": "更新",
         "checking": "检查中...",
         "offline": "离线",
+        "maintenance": "维护中",
+        "running": "运行中",
+        "launcherOutdated": "正在更新启动器...",
         "downloading": "下载中...",
         "pause": "暂停",
         "resume": "继续",
@@ -68,7 +83,8 @@ const STRINGS = {
         "language": "语言",
         "closeWindow": "关闭窗口",
         "minimizeToTray": "最小化到托盘",
-        "exitLauncher": "退出启动器"
+        "exitLauncher": "退出启动器",
+        "launchAtStartup": "开机时启动"
     }
 };
 let currentLang = "en";
@@ -158,6 +174,9 @@ window.addEventListener('DOMContentLoaded', async () => {
         });
     });
 
+    const elLaunchAtStartup = document.getElementById('launch-at-startup');
+    if (elLaunchAtStartup) elLaunchAtStartup.addEventListener('change', () => saveConfigOnly());
+
     // Logo click - open website
     const logoLink = document.getElementById('logo-link');
     if (logoLink) {
@@ -225,8 +244,60 @@ window.addEventListener('DOMContentLoaded', async () => {
         updateUI();
     });
 
+    // ganhrong://news/<id> links
+    listen('open-news', (event) => {
+        const i = newsData.findIndex(n => n.id === event.payload);
+        if (i < 0) return;
+        newsIndex = i;
+        updateNewsDisplay();
+        resetNewsInterval();
+    });
+
+    listen('deep-link-error', (event) => {
+        alert("Can't open link: " + event.payload);
+    });
+
+    // The watcher already stored the new manifest, render it without fetching again
     listen('manifest-updated', () => {
-        fetchManifest(true);
+        fetchManifest(false);
+    });
+
+    // Update watcher recomputed the status (new version, maintenance...)
+    listen('status-changed', (event) => {
+        if (typeof event.payload === 'string' && !gameStatus.startsWith("Downloading")) {
+            gameStatus = event.payload;
+            updateUI();
+        }
+    });
+
+    // Manifest needs a newer launcher, the backend is replacing itself and will restart
+    listen('launcher-update-progress', (event) => {
+        const p = event.payload;
+        gameStatus = "LauncherOutdated";
+        updateUI();
+        elProgressInfo.style.display = 'block';
+        elProgressText.innerText = p.status;
+        elProgressPercent.innerText = `${p.progress.toFixed(1)}%`;
+    });
+
+    listen('launcher-update-error', (event) => {
+        alert("Launcher update failed: " + event.payload);
+    });
+
+    // Backend tracks reachability of the manifest host
+    listen('connectivity-changed', (event) => {
+        if (event.payload === 'Online') {
+            elOfflineBanner.style.display = 'none';
+            if (gameStatus === "Offline" || gameStatus === "Error") fetchManifest(true);
+        } else {
+            elOfflineBanner.style.display = 'block';
+            // Same as the backend: a running game or install keeps its status
+            const inProgress = gameStatus.startsWith("Downloading") || ["Updating", "Running"].includes(gameStatus);
+            if (!inProgress) {
+                gameStatus = "Offline";
+                updateUI();
+            }
+        }
     });
 
     // Listen for language change from tray menu
@@ -291,12 +362,14 @@ function setLanguage(lang) {
     const elLabelCloseWindow = document.getElementById('label-close-window');
     const elLabelMinTray = document.getElementById('label-minimize-tray');
     const elLabelExit = document.getElementById('label-exit-launcher');
+    const elLabelStartup = document.getElementById('label-launch-at-startup');
 
     if (elSettingsTitle) elSettingsTitle.innerText = s.settings;
     if (elLabelLang) elLabelLang.innerText = s.language;
     if (elLabelCloseWindow) elLabelCloseWindow.innerText = s.closeWindow;
     if (elLabelMinTray) elLabelMinTray.innerText = s.minimizeToTray;
     if (elLabelExit) elLabelExit.innerText = s.exitLauncher;
+    if (elLabelStartup) elLabelStartup.innerText = s.launchAtStartup;
 
     // Update UI Texts
     updateUI();
@@ -309,14 +382,23 @@ async function saveConfigOnly() {
     let close = 'MinimizeToTray';
     for (const r of closeRadios) { if (r.checked) close = r.value; }
 
+    // Keep fields this screen doesn't edit (update interval etc.)
+    const elStartup = document.getElementById('launch-at-startup');
     const newConfig = {
+        ...currentConfig,
         language: currentLang,
         close_behavior: close,
-        launch_at_startup: false
+        launch_at_startup: elStartup ? elStartup.checked : !!currentConfig.launch_at_startup
     };
 
     currentConfig = newConfig;
-    await invoke('save_config', { config: newConfig });
+    try {
+        // The backend may correct values (e.g. a language the manifest doesn't offer)
+        currentConfig = await invoke('save_config', { config: newConfig });
+        if (currentConfig.language !== currentLang) setLanguage(currentConfig.language);
+    } catch (e) {
+        console.error("Failed to save settings:", e);
+    }
 }
 
 // Slideshow Logic
@@ -359,7 +441,11 @@ async function fetchManifest(force = false) {
 
         renderNews(latestManifest.news);
 
-        if (localVersion === "0.0.0") {
+        if (await invoke('get_status') === "LauncherOutdated") {
+            gameStatus = "LauncherOutdated";
+        } else if (latestManifest.server_status === "maintenance" || latestManifest.server_status === "closed") {
+            gameStatus = "Maintenance";
+        } else if (localVersion === "0.0.0") {
             gameStatus = "Missing";
         } else if (localVersion !== latestManifest.latest_version) {
             gameStatus = "UpdateAvailable";
@@ -371,7 +457,9 @@ async function fetchManifest(force = false) {
         hideLoadingOverlay();
     } catch (err) {
         hideLoadingOverlay();
-        if (err && err.toString().includes("Offline")) {
+        if (err && err.toString().includes("launcher is too old")) {
+            gameStatus = "LauncherOutdated";
+        } else if (err && err.toString().includes("Offline")) {
             gameStatus = "Offline";
             elOfflineBanner.style.display = 'block';
         } else {
@@ -392,6 +480,15 @@ function updateUI() {
     if (gameStatus === "Offline") {
         elBtnText.innerText = txt.offline;
         elActionBtn.disabled = true;
+    } else if (gameStatus === "Maintenance") {
+        elBtnText.innerText = txt.maintenance;
+        elActionBtn.disabled = true;
+    } else if (gameStatus === "LauncherOutdated") {
+        elBtnText.innerText = txt.launcherOutdated;
+        elActionBtn.disabled = true;
+    } else if (gameStatus === "Running") {
+        elBtnText.innerText = txt.running;
+        elActionBtn.disabled = true;
     } else if (gameStatus === "Missing") {
         elBtnText.innerText = txt.download;
     } else if (gameStatus === "UpdateAvailable") {
@@ -540,6 +637,8 @@ function applyConfig(cfg) {
         const r = document.querySelector(`input[name="close"][value="${cfg.close_behavior}"]`);
         if (r) r.checked = true;
     }
+    const elStartup = document.getElementById('launch-at-startup');
+    if (elStartup) elStartup.checked = !!cfg.launch_at_startup;
 }
 
 // Loading overlay functions